@@ -1,11 +1,12 @@
 use cosmwasm_schema::write_api;
 
-use cw404::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cw404::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
-        query: QueryMsg
+        query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
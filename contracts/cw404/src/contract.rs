@@ -1,41 +1,92 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128,
 };
 use cw2::set_contract_version;
-use cw20::Cw20Coin;
+use cw20::{Cw20Coin, Denom};
 
 use crate::error::ContractError;
-use crate::execute::execute_transfer_cw20;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::execute::{
+    execute_batch_transfer, execute_batch_transfer_nft, execute_buy_with_native,
+    execute_claim_airdrop, execute_claim_fraction_rewards, execute_claim_rewards,
+    execute_claim_vested, execute_commit_reveal, execute_create_vesting_schedule, execute_deposit,
+    execute_enable_trading, execute_freeze_metadata, execute_lock_nft, execute_receive,
+    execute_receive_nft, execute_reconcile, execute_redeem_nft, execute_reroll, execute_reveal,
+    execute_set_cw721_transfer_exempt, execute_set_fee_config, execute_set_fraction_staking_config,
+    execute_set_nft_metadata, execute_set_nft_order, execute_set_nft_staking_config,
+    execute_set_reroll_fee, execute_set_royalty_info, execute_set_sale_config,
+    execute_set_token_royalty_info, execute_set_token_uri, execute_set_trait_layers,
+    execute_set_whitelist_phase, execute_stake_fractions, execute_stake_nft, execute_transfer_cw20,
+    execute_unlock_nft, execute_unstake_fractions, execute_unstake_nft,
+    execute_update_base_token_uri, execute_whitelist_buy, execute_withdraw,
+    execute_withdraw_proceeds,
+};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, VestingScheduleMsg};
 use crate::query::{
-    query_balance, query_cw721_transfer_exempt, query_max_nft_supply, query_nft_num_token,
-    query_owner_of, query_token_info,
+    query_all_accounts, query_all_nft_info, query_balance, query_balance_at_height,
+    query_bank_size, query_banked_nfts, query_check_invariants, query_check_royalties,
+    query_current_price, query_cw721_transfer_exempt, query_erc721_balance_of, query_fee_config,
+    query_fraction_stake, query_fraction_staking_config, query_holder_count, query_is_revealed,
+    query_launch_info, query_locked_nfts, query_max_nft_supply, query_next_from_bank,
+    query_nft_balance, query_nft_balance_at_height, query_nft_holders, query_nft_info,
+    query_nft_num_token, query_nft_order, query_nft_stakes, query_nft_staking_config,
+    query_owner_of, query_pending_nft_rewards, query_rarity_of, query_render_svg, query_reroll_fee,
+    query_royalty_info, query_sale_info, query_token_info, query_total_supply_at_height,
+    query_vesting, query_whitelist_claimed, query_whitelist_phases,
 };
 use crate::state::{
-    TokenInfo, BALANCES, CW721_TRANSFER_EXEMPT, MAX_NFT_SUPPLY, NFT_COUNT, TOKEN_INFO,
+    FeeConfig, LaunchInfo, MetadataConfig, RevealInfo, SaleConfig, TokenInfo, VestingSchedule,
+    WhitelistPhase, BALANCES, COLLECTION_SEED, CW721_TRANSFER_EXEMPT, FEE_CONFIG, HOLDER_COUNT,
+    LAUNCH_INFO, LIVE_NFT_COUNT, MAX_NFT_SUPPLY, METADATA_CONFIG, NFT_BALANCES, NFT_COUNT,
+    NFT_TOKENS, REVEAL_INFO, TOKEN_INFO, TOTAL_SUPPLY_HISTORY, TRAIT_LAYERS, VESTING_SCHEDULES,
 };
+use crate::traits::{collection_seed, validate_trait_layers};
 
 // version info for migration info
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// token uris default to the base token uri followed by the token id
+const DEFAULT_TOKEN_URI_TEMPLATE: &str = "{base}{id}";
 
 // instantiate contract
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     // check valid token info
     msg.validate()?;
+    let trait_layers = msg.trait_layers.unwrap_or_default();
+    validate_trait_layers(&trait_layers)?;
     // create initial accounts
     let units = Uint128::from(10u128.pow(u32::from(msg.decimals)));
-    let total_supply = create_accounts(&mut deps, &msg.initial_balances, units)?;
+    let mut total_supply = create_accounts(&mut deps, &env, &msg.initial_balances, units)?;
+    total_supply += create_vesting_schedules(&mut deps, &env, &msg.vesting_schedules, units)?;
     let admin = deps.api.addr_validate(&msg.admin)?;
+    let underlying = match msg.underlying {
+        Some(Denom::Cw20(address)) => {
+            let address = deps.api.addr_validate(address.as_str())?;
+            if address == env.contract.address {
+                return Err(ContractError::InvalidUnderlying {});
+            }
+            Some(Denom::Cw20(address))
+        }
+        underlying => underlying,
+    };
+    let wrapped_collection = msg
+        .wrapped_collection
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    if underlying.is_some() && wrapped_collection.is_some() {
+        return Err(ContractError::InvalidUnderlying {});
+    }
+    if (underlying.is_some() || wrapped_collection.is_some()) && !total_supply.is_zero() {
+        return Err(ContractError::UnbackedSupply {});
+    }
 
     let data = TokenInfo {
         name: msg.name,
@@ -45,33 +96,139 @@ pub fn instantiate(
         admin: admin.clone(),
         units,
         base_token_uri: msg.base_token_uri,
+        underlying,
+        wrapped_collection,
     };
     TOKEN_INFO.save(deps.storage, &data)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &total_supply, env.block.height)?;
     MAX_NFT_SUPPLY.save(deps.storage, &(total_supply / units))?;
     NFT_COUNT.save(deps.storage, &0)?;
+    LIVE_NFT_COUNT.save(deps.storage, &0)?;
     CW721_TRANSFER_EXEMPT.save(deps.storage, &admin, &true)?;
+    // staked fractions are held by the contract, it does not hold Cw721s
+    CW721_TRANSFER_EXEMPT.save(deps.storage, &env.contract.address, &true)?;
+    // trading stays closed for non exempt addresses until the admin enables it
+    LAUNCH_INFO.save(
+        deps.storage,
+        &LaunchInfo {
+            trading_enabled: false,
+            launch_height: None,
+            cooldown_blocks: 0,
+        },
+    )?;
+    // no fees until the admin configures them
+    FEE_CONFIG.save(
+        deps.storage,
+        &FeeConfig {
+            buy_fee_bps: 0,
+            sell_fee_bps: 0,
+            transfer_fee_bps: 0,
+            treasury: admin,
+        },
+    )?;
+    // traits of each newly minted token are derived from the collection seed and its id
+    TRAIT_LAYERS.save(deps.storage, &trait_layers)?;
+    COLLECTION_SEED.save(deps.storage, &collection_seed(&env))?;
+    // without a placeholder uri the collection is revealed from the start
+    REVEAL_INFO.save(
+        deps.storage,
+        &RevealInfo {
+            revealed: msg.placeholder_token_uri.is_none(),
+            placeholder_uri: msg.placeholder_token_uri,
+            seed_hash: None,
+            offset: None,
+            revealed_supply: None,
+        },
+    )?;
+    METADATA_CONFIG.save(
+        deps.storage,
+        &MetadataConfig {
+            token_uri_template: DEFAULT_TOKEN_URI_TEMPLATE.to_string(),
+            frozen: false,
+        },
+    )?;
 
     Ok(Response::default())
 }
 
 pub fn create_accounts(
     deps: &mut DepsMut,
+    env: &Env,
     accounts: &[Cw20Coin],
     units: Uint128,
 ) -> Result<Uint128, ContractError> {
     validate_accounts(accounts)?;
 
     let mut total_supply = Uint128::zero();
+    let mut holder_count = 0u64;
     for account in accounts {
         let address = deps.api.addr_validate(&account.address)?;
         let ammout = account.amount.checked_mul(units)?;
-        BALANCES.save(deps.storage, &address, &ammout)?;
+        BALANCES.save(deps.storage, &address, &ammout, env.block.height)?;
         total_supply += ammout;
+        if !ammout.is_zero() {
+            holder_count += 1;
+        }
     }
+    HOLDER_COUNT.save(deps.storage, &holder_count)?;
 
     Ok(total_supply)
 }
 
+// Vesting allocations are minted into the contract's exempt balance, their amounts are in whole
+// units like the initial balances
+pub fn create_vesting_schedules(
+    deps: &mut DepsMut,
+    env: &Env,
+    schedules: &Option<Vec<VestingScheduleMsg>>,
+    units: Uint128,
+) -> Result<Uint128, ContractError> {
+    let Some(schedules) = schedules else {
+        return Ok(Uint128::zero());
+    };
+
+    let mut total_vesting = Uint128::zero();
+    for schedule in schedules {
+        let beneficiary = deps.api.addr_validate(&schedule.beneficiary)?;
+        if VESTING_SCHEDULES.has(deps.storage, &beneficiary) {
+            return Err(ContractError::VestingScheduleExists {
+                beneficiary: beneficiary.to_string(),
+            });
+        }
+        let amount = schedule.amount.checked_mul(units)?;
+        let vesting_schedule = VestingSchedule {
+            total: amount,
+            claimed: Uint128::zero(),
+            start_height: env.block.height,
+            start_time: env.block.time,
+            cliff: schedule.cliff,
+            duration: schedule.duration,
+        };
+        if !vesting_schedule.is_valid() {
+            return Err(ContractError::InvalidVestingSchedule {});
+        }
+        VESTING_SCHEDULES.save(deps.storage, &beneficiary, &vesting_schedule)?;
+        total_vesting += amount;
+    }
+
+    if !total_vesting.is_zero() {
+        let balance = BALANCES
+            .may_load(deps.storage, &env.contract.address)?
+            .unwrap_or_default();
+        if balance.is_zero() {
+            HOLDER_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+        }
+        BALANCES.save(
+            deps.storage,
+            &env.contract.address,
+            &(balance + total_vesting),
+            env.block.height,
+        )?;
+    }
+
+    Ok(total_vesting)
+}
+
 pub fn validate_accounts(accounts: &[Cw20Coin]) -> Result<(), ContractError> {
     let mut addresses = accounts.iter().map(|c| &c.address).collect::<Vec<_>>();
     addresses.sort();
@@ -96,6 +253,140 @@ pub fn execute(
         ExecuteMsg::Transfer { recipient, amount } => {
             execute_transfer_cw20(deps, env, info, recipient, amount)
         }
+        ExecuteMsg::BatchTransfer { transfers } => {
+            execute_batch_transfer(deps, env, info, transfers)
+        }
+        ExecuteMsg::BatchTransferNft { transfers } => {
+            execute_batch_transfer_nft(deps, env, info, transfers)
+        }
+        ExecuteMsg::EnableTrading {
+            launch_height,
+            cooldown_blocks,
+        } => execute_enable_trading(deps, env, info, launch_height, cooldown_blocks),
+        ExecuteMsg::SetCw721TransferExempt { address, state } => {
+            execute_set_cw721_transfer_exempt(deps, info, address, state)
+        }
+        ExecuteMsg::SetFeeConfig {
+            buy_fee_bps,
+            sell_fee_bps,
+            transfer_fee_bps,
+            treasury,
+        } => execute_set_fee_config(
+            deps,
+            info,
+            buy_fee_bps,
+            sell_fee_bps,
+            transfer_fee_bps,
+            treasury,
+        ),
+        ExecuteMsg::SetRoyaltyInfo { royalty } => execute_set_royalty_info(deps, info, royalty),
+        ExecuteMsg::SetTokenRoyaltyInfo { token_id, royalty } => {
+            execute_set_token_royalty_info(deps, info, token_id, royalty)
+        }
+        ExecuteMsg::SetNftMetadata {
+            token_id,
+            extension,
+        } => execute_set_nft_metadata(deps, info, token_id, extension),
+        ExecuteMsg::SetTraitLayers { layers } => execute_set_trait_layers(deps, info, layers),
+        ExecuteMsg::CommitReveal { seed_hash } => execute_commit_reveal(deps, info, seed_hash),
+        ExecuteMsg::Reveal { seed } => execute_reveal(deps, info, seed),
+        ExecuteMsg::UpdateBaseTokenUri {
+            base_token_uri,
+            token_uri_template,
+        } => execute_update_base_token_uri(deps, info, base_token_uri, token_uri_template),
+        ExecuteMsg::SetTokenUri {
+            token_id,
+            token_uri,
+        } => execute_set_token_uri(deps, info, token_id, token_uri),
+        ExecuteMsg::FreezeMetadata {} => execute_freeze_metadata(deps, info),
+        ExecuteMsg::Reconcile { address } => execute_reconcile(deps, env, info, address),
+        ExecuteMsg::LockNft { token_id } => execute_lock_nft(deps, info, token_id),
+        ExecuteMsg::UnlockNft { token_id } => execute_unlock_nft(deps, info, token_id),
+        ExecuteMsg::SetNftOrder { token_ids } => execute_set_nft_order(deps, info, token_ids),
+        ExecuteMsg::SetNftStakingConfig {
+            reward_denom,
+            reward_per_block,
+            unbonding_period,
+        } => execute_set_nft_staking_config(
+            deps,
+            env,
+            info,
+            reward_denom,
+            reward_per_block,
+            unbonding_period,
+        ),
+        ExecuteMsg::StakeNft { token_id } => execute_stake_nft(deps, env, info, token_id),
+        ExecuteMsg::UnstakeNft { token_id } => execute_unstake_nft(deps, env, info, token_id),
+        ExecuteMsg::ClaimRewards {} => execute_claim_rewards(deps, env, info),
+        ExecuteMsg::SetFractionStakingConfig {
+            reward_denom,
+            reward_per_block,
+        } => execute_set_fraction_staking_config(deps, env, info, reward_denom, reward_per_block),
+        ExecuteMsg::StakeFractions { amount } => execute_stake_fractions(deps, env, info, amount),
+        ExecuteMsg::UnstakeFractions { amount } => {
+            execute_unstake_fractions(deps, env, info, amount)
+        }
+        ExecuteMsg::ClaimFractionRewards {} => execute_claim_fraction_rewards(deps, env, info),
+        ExecuteMsg::SetSaleConfig {
+            denom,
+            pricing,
+            start_time,
+            end_time,
+            per_wallet_cap,
+            total_cap,
+        } => execute_set_sale_config(
+            deps,
+            info,
+            SaleConfig {
+                denom,
+                pricing,
+                start_time,
+                end_time,
+                per_wallet_cap,
+                total_cap,
+                sold: Uint128::zero(),
+                proceeds: Uint128::zero(),
+            },
+        ),
+        ExecuteMsg::BuyWithNative { amount } => execute_buy_with_native(deps, env, info, amount),
+        ExecuteMsg::SetWhitelistPhase {
+            phase_id,
+            merkle_root,
+            start_time,
+            end_time,
+            price_per_unit,
+        } => execute_set_whitelist_phase(
+            deps,
+            info,
+            phase_id,
+            WhitelistPhase {
+                merkle_root,
+                start_time,
+                end_time,
+                price_per_unit,
+                proceeds: Uint128::zero(),
+            },
+        ),
+        ExecuteMsg::ClaimAirdrop { amount, proof } => {
+            execute_claim_airdrop(deps, env, info, amount, proof)
+        }
+        ExecuteMsg::WhitelistBuy { amount, proof } => {
+            execute_whitelist_buy(deps, env, info, amount, proof)
+        }
+        ExecuteMsg::CreateVestingSchedule { schedule } => {
+            execute_create_vesting_schedule(deps, env, info, schedule)
+        }
+        ExecuteMsg::ClaimVested {} => execute_claim_vested(deps, env, info),
+        ExecuteMsg::WithdrawProceeds { recipient } => {
+            execute_withdraw_proceeds(deps, info, recipient)
+        }
+        ExecuteMsg::SetRerollFee { fee } => execute_set_reroll_fee(deps, info, fee),
+        ExecuteMsg::Reroll { token_id } => execute_reroll(deps, env, info, token_id),
+        ExecuteMsg::Deposit {} => execute_deposit(deps, env, info),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::Withdraw { amount } => execute_withdraw(deps, env, info, amount),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
+        ExecuteMsg::RedeemNft { token_id } => execute_redeem_nft(deps, env, info, token_id),
     }
 }
 
@@ -106,8 +397,43 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         // cw20 query
         QueryMsg::Balance { address } => to_json_binary(&query_balance(deps, address)?),
         QueryMsg::TokenInfo {} => to_json_binary(&query_token_info(deps)?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_json_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::HolderCount {} => to_json_binary(&query_holder_count(deps)?),
+        QueryMsg::BalanceAtHeight { address, height } => {
+            to_json_binary(&query_balance_at_height(deps, address, height)?)
+        }
+        QueryMsg::TotalSupplyAtHeight { height } => {
+            to_json_binary(&query_total_supply_at_height(deps, height)?)
+        }
 
         // cw721 query
+        QueryMsg::CheckInvariants { start_after, limit } => {
+            to_json_binary(&query_check_invariants(deps, start_after, limit)?)
+        }
+        QueryMsg::NftBalance { owner } => to_json_binary(&query_nft_balance(deps, owner)?),
+        QueryMsg::LockedNfts { owner } => to_json_binary(&query_locked_nfts(deps, owner)?),
+        QueryMsg::NftOrder { owner } => to_json_binary(&query_nft_order(deps, owner)?),
+        QueryMsg::NftHolders { start_after, limit } => {
+            to_json_binary(&query_nft_holders(deps, start_after, limit)?)
+        }
+        QueryMsg::Erc721BalanceOf { owner } => {
+            to_json_binary(&query_erc721_balance_of(deps, owner)?)
+        }
+        QueryMsg::NftBalanceAtHeight { address, height } => {
+            to_json_binary(&query_nft_balance_at_height(deps, address, height)?)
+        }
+        QueryMsg::NftInfo { token_id } => to_json_binary(&query_nft_info(deps, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_json_binary(&query_all_nft_info(
+            deps,
+            env,
+            token_id,
+            include_expired.unwrap_or(false),
+        )?),
         QueryMsg::NftNumTokens {} => to_json_binary(&query_nft_num_token(deps)?),
         QueryMsg::MaxNftSupply {} => to_json_binary(&query_max_nft_supply(deps)?),
         QueryMsg::Cw721TransferExempt { address } => {
@@ -122,5 +448,163 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             token_id,
             include_expired.unwrap_or(false),
         )?),
+
+        // launch query
+        QueryMsg::LaunchInfo {} => to_json_binary(&query_launch_info(deps)?),
+
+        // fee query
+        QueryMsg::FeeConfig {} => to_json_binary(&query_fee_config(deps)?),
+
+        // cw2981 query
+        QueryMsg::RoyaltyInfo {
+            token_id,
+            sale_price,
+        } => to_json_binary(&query_royalty_info(deps, token_id, sale_price)?),
+        QueryMsg::CheckRoyalties {} => to_json_binary(&query_check_royalties(deps)?),
+
+        // trait query
+        QueryMsg::RarityOf { token_id } => to_json_binary(&query_rarity_of(deps, token_id)?),
+
+        // reveal query
+        QueryMsg::IsRevealed {} => to_json_binary(&query_is_revealed(deps)?),
+
+        // render query
+        QueryMsg::RenderSvg { token_id } => to_json_binary(&query_render_svg(deps, token_id)?),
+        // nft staking query
+        QueryMsg::NftStakingConfig {} => to_json_binary(&query_nft_staking_config(deps)?),
+        QueryMsg::NftStakes {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query_nft_stakes(deps, env, owner, start_after, limit)?),
+        QueryMsg::PendingNftRewards { owner } => {
+            to_json_binary(&query_pending_nft_rewards(deps, env, owner)?)
+        }
+        // fraction staking query
+        QueryMsg::FractionStakingConfig {} => to_json_binary(&query_fraction_staking_config(deps)?),
+        QueryMsg::FractionStake { address } => {
+            to_json_binary(&query_fraction_stake(deps, env, address)?)
+        }
+        // sale query
+        QueryMsg::SaleInfo {} => to_json_binary(&query_sale_info(deps, env)?),
+        QueryMsg::CurrentPrice { amount } => {
+            to_json_binary(&query_current_price(deps, env, amount)?)
+        }
+        QueryMsg::WhitelistPhases { start_after, limit } => {
+            to_json_binary(&query_whitelist_phases(deps, env, start_after, limit)?)
+        }
+        QueryMsg::WhitelistClaimed { phase_id, address } => {
+            to_json_binary(&query_whitelist_claimed(deps, phase_id, address)?)
+        }
+        // vesting query
+        QueryMsg::Vesting { beneficiary } => {
+            to_json_binary(&query_vesting(deps, env, beneficiary)?)
+        }
+        // reroll query
+        QueryMsg::RerollFee {} => to_json_binary(&query_reroll_fee(deps)?),
+        // bank query
+        QueryMsg::BankedNfts { start, limit } => {
+            to_json_binary(&query_banked_nfts(deps, start, limit)?)
+        }
+        QueryMsg::BankSize {} => to_json_binary(&query_bank_size(deps)?),
+        QueryMsg::NextFromBank {} => to_json_binary(&query_next_from_bank(deps)?),
+    }
+}
+
+// migrate contract
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CW721_TRANSFER_EXEMPT.save(deps.storage, &env.contract.address, &true)?;
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+
+    // Items added since the previous version get defaults fitting a live token:
+    // trading open, no fees, revealed, no trait layers
+    if LAUNCH_INFO.may_load(deps.storage)?.is_none() {
+        LAUNCH_INFO.save(
+            deps.storage,
+            &LaunchInfo {
+                trading_enabled: true,
+                launch_height: Some(env.block.height),
+                cooldown_blocks: 0,
+            },
+        )?;
+    }
+    if FEE_CONFIG.may_load(deps.storage)?.is_none() {
+        FEE_CONFIG.save(
+            deps.storage,
+            &FeeConfig {
+                buy_fee_bps: 0,
+                sell_fee_bps: 0,
+                transfer_fee_bps: 0,
+                treasury: token_info.admin.clone(),
+            },
+        )?;
+    }
+    if TRAIT_LAYERS.may_load(deps.storage)?.is_none() {
+        TRAIT_LAYERS.save(deps.storage, &vec![])?;
+    }
+    if COLLECTION_SEED.may_load(deps.storage)?.is_none() {
+        COLLECTION_SEED.save(deps.storage, &collection_seed(&env))?;
     }
+    if REVEAL_INFO.may_load(deps.storage)?.is_none() {
+        REVEAL_INFO.save(
+            deps.storage,
+            &RevealInfo {
+                revealed: true,
+                placeholder_uri: None,
+                seed_hash: None,
+                offset: None,
+                revealed_supply: None,
+            },
+        )?;
+    }
+    if TOTAL_SUPPLY_HISTORY.may_load(deps.storage)?.is_none() {
+        TOTAL_SUPPLY_HISTORY.save(deps.storage, &token_info.total_supply, env.block.height)?;
+    }
+
+    if METADATA_CONFIG.may_load(deps.storage)?.is_none() {
+        METADATA_CONFIG.save(
+            deps.storage,
+            &MetadataConfig {
+                token_uri_template: DEFAULT_TOKEN_URI_TEMPLATE.to_string(),
+                frozen: false,
+            },
+        )?;
+    }
+
+    let holder_count = BALANCES
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter(|item| matches!(item, Ok((_, balance)) if !balance.is_zero()))
+        .count() as u64;
+    HOLDER_COUNT.save(deps.storage, &holder_count)?;
+
+    // Token uris are built at query time, strip the ones persisted at mint time.
+    // Per owner and live nft counts are rebuilt along the way.
+    let owners = NFT_BALANCES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for owner in owners {
+        NFT_BALANCES.remove(deps.storage, &owner);
+    }
+    let token_ids = NFT_TOKENS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    LIVE_NFT_COUNT.save(deps.storage, &(token_ids.len() as u64))?;
+    let mut stripped = 0u64;
+    for token_id in token_ids {
+        let mut nft_info = NFT_TOKENS.load(deps.storage, &token_id)?;
+        NFT_BALANCES.update(deps.storage, &nft_info.owner, |count| -> StdResult<_> {
+            Ok(count.unwrap_or_default() + 1)
+        })?;
+        if nft_info.token_uri.take().is_some() {
+            NFT_TOKENS.save(deps.storage, &token_id, &nft_info)?;
+            stripped += 1;
+        }
+    }
+
+    let resp = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("stripped_token_uris", stripped.to_string());
+    Ok(resp)
 }
@@ -1,4 +1,5 @@
-use cosmwasm_std::{StdError, OverflowError};
+use cosmwasm_std::{CheckedMultiplyFractionError, StdError, OverflowError, Uint128};
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -12,6 +13,153 @@ pub enum ContractError {
     #[error("{0}")]
     OverflowError(#[from] OverflowError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    CheckedMultiplyFraction(#[from] CheckedMultiplyFractionError),
+
     #[error("token_id already claimed")]
     Claimed {},
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Trading is not enabled yet")]
+    TradingNotEnabled {},
+
+    #[error("{address} already received tokens in this block")]
+    TransferCooldown { address: String },
+
+    #[error("Fee rate must not exceed {max_bps} basis points")]
+    FeeTooHigh { max_bps: u16 },
+
+    #[error("Royalty percentage must be between 0 and 100")]
+    InvalidRoyaltyPercentage {},
+
+    #[error("Invalid token_id {token_id}")]
+    InvalidTokenId { token_id: String },
+
+    #[error("Trait layer {trait_type} must have a name and a positive total weight")]
+    InvalidTraitLayer { trait_type: String },
+
+    #[error("Collection is already revealed")]
+    AlreadyRevealed {},
+
+    #[error("Reveal seed does not match the committed seed hash")]
+    InvalidRevealSeed {},
+
+    #[error("Metadata is frozen")]
+    MetadataFrozen {},
+
+    #[error("Token uri template must contain {{id}}")]
+    InvalidTokenUriTemplate {},
+
+    #[error("Collection must be revealed before freezing metadata")]
+    NotRevealed {},
+
+    #[error("No NFT left in the bank or to mint for a reroll")]
+    NothingToReroll {},
+
+    #[error("Reroll fee must be below one unit of fractions")]
+    InvalidRerollFee {},
+
+    #[error("Reroll fee would cost a whole unit of the balance")]
+    InsufficientFractions {},
+
+    #[error("Reroll fee must be at least {amount}")]
+    InsufficientRerollFee { amount: Uint128 },
+
+    #[error("NFT {token_id} is locked")]
+    NftLocked { token_id: String },
+
+    #[error("NFT {token_id} is not locked")]
+    NftNotLocked { token_id: String },
+
+    #[error("{address} only holds locked or staked NFTs, unlock or unstake one to transfer")]
+    OnlyLockedNfts { address: String },
+
+    #[error("Duplicate token_id {token_id}")]
+    DuplicateTokenId { token_id: String },
+
+    #[error("NFT staking is not configured")]
+    StakingNotConfigured {},
+
+    #[error("NFT {token_id} is staked")]
+    NftStaked { token_id: String },
+
+    #[error("NFT {token_id} is not staked")]
+    NftNotStaked { token_id: String },
+
+    #[error("Balance backing {staked} staked NFTs can not be moved")]
+    StakedBalance { staked: u64 },
+
+    #[error("No rewards to claim")]
+    NothingToClaim {},
+
+    #[error("Rewards can not be paid in this token")]
+    InvalidRewardDenom {},
+
+    #[error("Fraction staking is not configured")]
+    FractionStakingNotConfigured {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Only {staked} fractions are staked")]
+    InsufficientStake { staked: Uint128 },
+
+    #[error("Sale must have a valid pricing and end after it starts")]
+    InvalidSaleConfig {},
+
+    #[error("Sale is not configured")]
+    SaleNotConfigured {},
+
+    #[error("Sale is not active")]
+    SaleNotActive {},
+
+    #[error("Sale cap reached")]
+    SaleCapReached {},
+
+    #[error("Paid amount does not cover the cost")]
+    InsufficientPayment {},
+
+    #[error("Merkle root must be a 32 bytes sha256 hash")]
+    InvalidMerkleRoot {},
+
+    #[error("Whitelist phase must end after it starts")]
+    InvalidWhitelistPhase {},
+
+    #[error("Proof does not match the allocation in any active phase")]
+    InvalidMerkleProof {},
+
+    #[error("Allocation already claimed")]
+    AllocationClaimed {},
+
+    #[error("Vesting schedule must have an amount, a duration and a cliff within it, in the same kind")]
+    InvalidVestingSchedule {},
+
+    #[error("{beneficiary} already has a vesting schedule")]
+    VestingScheduleExists { beneficiary: String },
+
+    #[error("Batch is empty")]
+    EmptyBatch {},
+
+    #[error("Batch item {index} failed: {error}")]
+    BatchItemFailed { index: u64, error: String },
+
+    #[error("Contract does not wrap an underlying asset")]
+    NotWrapper {},
+
+    #[error("Only the underlying asset can be deposited")]
+    InvalidUnderlying {},
+
+    #[error("Fractions of a wrapper can only be minted by deposits")]
+    UnbackedSupply {},
+
+    #[error("No NFT in the bank to hand out")]
+    EmptyBank {},
+
+    #[error("Minting another NFT would exceed the max NFT supply")]
+    MaxNftSupplyReached {},
 }
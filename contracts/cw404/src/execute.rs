@@ -1,15 +1,38 @@
-use std::str::FromStr;
+use std::collections::BTreeMap;
 
-use cosmwasm_std::{Attribute, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128};
+use cosmwasm_std::{
+    to_json_binary, Addr, Attribute, BankMsg, Binary, Coin, CosmosMsg, Decimal, DepsMut, Empty,
+    Env, MessageInfo, Order, Response, StdResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg};
 use cw_storage_plus::Bound;
+use cw_utils::{must_pay, nonpayable, Duration};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
+use crate::merkle::{merkle_leaf, verify_merkle_proof};
+use crate::msg::{RoyaltyInfoMsg, VestingScheduleMsg};
+use crate::sale::{is_valid_pricing, max_affordable_amount, sale_cost};
 use crate::state::{
-    NftInfo, BALANCES, CW721_TRANSFER_EXEMPT, DEQUE_NFT, NFT_COUNT, NFT_TOKENS, TOKEN_INFO,
+    Extension, FeeConfig, FractionRewardIndex, FractionStake, FractionStakingConfig, LaunchInfo,
+    Metadata, NftInfo, NftStake, NftStakingConfig, RerollFee, RewardIndex, RoyaltyInfo, SaleConfig,
+    TokenInfo, Trait, TraitLayer, VestingSchedule, WhitelistPhase, BALANCES, BANKED_EXTENSIONS,
+    CW721_TRANSFER_EXEMPT, DEQUE_NFT, FEE_CONFIG, FRACTION_REWARD_INDEX, FRACTION_STAKES,
+    FRACTION_STAKING_CONFIG, HOLDER_COUNT, LAST_TRANSFER_HEIGHT, LAUNCH_INFO, LIVE_NFT_COUNT,
+    LOCKED_NFTS, MAX_NFT_SUPPLY, METADATA_CONFIG, NFT_BALANCES, NFT_COUNT, NFT_ORDER,
+    NFT_REWARD_INDEX, NFT_STAKES, NFT_STAKING_CONFIG, NFT_STAKING_REWARDS, NFT_TOKENS, REROLL_FEE,
+    REVEAL_INFO, ROYALTY_INFO, SALE_CONFIG, SALE_PURCHASES, TOKEN_INFO, TOKEN_ROYALTY_INFO,
+    TOKEN_URI_OVERRIDES, TOTAL_SUPPLY_HISTORY, TRAIT_LAYERS, VESTING_SCHEDULES, WHITELIST_CLAIMED,
+    WHITELIST_PHASES, WRAPPED_NFTS,
 };
+use crate::traits::{generate_traits, validate_trait_layers};
 
-const DEFAULT_LIMIT: u32 = 10;
-const MAX_LIMIT: u32 = 1000;
+// hard caps on fee rates, in basis points
+const MAX_TRADE_FEE_BPS: u16 = 1000;
+const MAX_TRANSFER_FEE_BPS: u16 = 500;
+const BPS_DENOMINATOR: u128 = 10000;
 
 pub fn execute_transfer_cw20(
     deps: DepsMut,
@@ -23,6 +46,640 @@ pub fn execute_transfer_cw20(
     _tranfer_cw20_with_cw721(deps, env, info, recipient_addr.to_string(), amount)
 }
 
+// Token uri and metadata of a wrapped collection's token. Fields of richer metadata schemas
+// are ignored instead of rejected, as Metadata would.
+#[derive(Deserialize)]
+struct WrappedNftInfo {
+    token_uri: Option<String>,
+    #[serde(default)]
+    extension: Option<WrappedMetadata>,
+}
+
+#[derive(Deserialize)]
+struct WrappedMetadata {
+    name: Option<String>,
+    description: Option<String>,
+    image: Option<String>,
+    attributes: Option<Vec<WrappedTrait>>,
+    animation_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WrappedTrait {
+    display_type: Option<String>,
+    trait_type: String,
+    value: String,
+}
+
+impl From<WrappedMetadata> for Metadata {
+    fn from(metadata: WrappedMetadata) -> Self {
+        Metadata {
+            name: metadata.name,
+            description: metadata.description,
+            image: metadata.image,
+            attributes: metadata.attributes.map(|attributes| {
+                attributes
+                    .into_iter()
+                    .map(|attribute| Trait {
+                        display_type: attribute.display_type,
+                        trait_type: attribute.trait_type,
+                        value: attribute.value,
+                    })
+                    .collect()
+            }),
+            animation_url: metadata.animation_url,
+        }
+    }
+}
+
+// An address touched by a batch, NFTs are settled once for its net change in whole units
+struct BatchAccount {
+    is_cw721_exempt: bool,
+    units_before: Uint128,
+    // NFTs already moved in or out directly by the batch
+    nfts_moved: i128,
+}
+
+pub fn execute_batch_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<(String, Uint128)>,
+) -> Result<Response, ContractError> {
+    if transfers.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    let ctx = _transfer_context(&deps, &info.sender)?;
+    let token_info = &ctx.token_info;
+    let mut accounts = BTreeMap::new();
+    _batch_account(&deps, token_info, &mut accounts, &info.sender)?;
+    _batch_account(&deps, token_info, &mut accounts, &ctx.fee_config.treasury)?;
+
+    let mut total_amount = Uint128::zero();
+    let mut total_fee = Uint128::zero();
+    for (index, (recipient, amount)) in transfers.iter().enumerate() {
+        let mut batch_item = || -> Result<Uint128, ContractError> {
+            let recipient = deps.api.addr_validate(recipient)?;
+            let is_recipient_cw721_exempt =
+                _batch_account(&deps, token_info, &mut accounts, &recipient)?;
+            _check_launch(&mut deps, &env, &ctx, &recipient, is_recipient_cw721_exempt)?;
+            let (fee_amount, _) = _take_fee(
+                &mut deps,
+                &env,
+                &info,
+                &ctx,
+                &recipient,
+                is_recipient_cw721_exempt,
+                *amount,
+            )?;
+            _tranfer_cw20(
+                &mut deps,
+                &env,
+                &info,
+                recipient.to_string(),
+                amount.checked_sub(fee_amount)?,
+            )?;
+            Ok(fee_amount)
+        };
+        let fee_amount = batch_item().map_err(|err| ContractError::BatchItemFailed {
+            index: index as u64,
+            error: err.to_string(),
+        })?;
+        total_amount += amount;
+        total_fee += fee_amount;
+    }
+
+    let (withdrawn, issued) = _settle_batch(&mut deps, &env, &info, token_info, &accounts)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "batch transfer")
+        .add_attribute("from", info.sender)
+        .add_attribute("count", transfers.len().to_string())
+        .add_attribute("amount", total_amount)
+        .add_attribute("fee_amount", total_fee)
+        .add_attribute("nfts_withdrawn", withdrawn.to_string())
+        .add_attribute("nfts_issued", issued.to_string());
+    Ok(resp)
+}
+
+pub fn execute_batch_transfer_nft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<(String, String)>,
+) -> Result<Response, ContractError> {
+    if transfers.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    let ctx = _transfer_context(&deps, &info.sender)?;
+    let token_info = &ctx.token_info;
+    let mut accounts = BTreeMap::new();
+    _batch_account(&deps, token_info, &mut accounts, &info.sender)?;
+    _batch_account(&deps, token_info, &mut accounts, &ctx.fee_config.treasury)?;
+
+    let mut total_fee = Uint128::zero();
+    for (index, (recipient, token_id)) in transfers.iter().enumerate() {
+        let mut batch_item = || -> Result<Uint128, ContractError> {
+            let recipient = deps.api.addr_validate(recipient)?;
+            let nft_token = NFT_TOKENS.load(deps.storage, token_id)?;
+            if nft_token.owner != info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            if LOCKED_NFTS.has(deps.storage, (&info.sender, token_id)) {
+                return Err(ContractError::NftLocked {
+                    token_id: token_id.clone(),
+                });
+            }
+            if _is_nft_protected(&deps, &env, &info.sender, token_id)? {
+                return Err(ContractError::NftStaked {
+                    token_id: token_id.clone(),
+                });
+            }
+            let is_recipient_cw721_exempt =
+                _batch_account(&deps, token_info, &mut accounts, &recipient)?;
+            _check_launch(&mut deps, &env, &ctx, &recipient, is_recipient_cw721_exempt)?;
+
+            // The fee is paid on top, so the NFT moves with exactly one unit
+            let (fee_amount, _) = _take_fee(
+                &mut deps,
+                &env,
+                &info,
+                &ctx,
+                &recipient,
+                is_recipient_cw721_exempt,
+                token_info.units,
+            )?;
+            _tranfer_cw20(
+                &mut deps,
+                &env,
+                &info,
+                recipient.to_string(),
+                token_info.units,
+            )?;
+
+            // An exempt recipient holds no NFTs, the token goes to the bank instead
+            if is_recipient_cw721_exempt {
+                DEQUE_NFT.push_front(deps.storage, token_id)?;
+                _burn_nft(&mut deps, &info, token_id.clone())?;
+            } else {
+                _move_nft(&mut deps, token_id, &recipient)?;
+                if let Some(account) = accounts.get_mut(&recipient) {
+                    account.nfts_moved += 1;
+                }
+            }
+            if let Some(account) = accounts.get_mut(&info.sender) {
+                account.nfts_moved -= 1;
+            }
+            Ok(fee_amount)
+        };
+        let fee_amount = batch_item().map_err(|err| ContractError::BatchItemFailed {
+            index: index as u64,
+            error: err.to_string(),
+        })?;
+        total_fee += fee_amount;
+    }
+
+    let (withdrawn, issued) = _settle_batch(&mut deps, &env, &info, token_info, &accounts)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "batch transfer nft")
+        .add_attribute("from", info.sender)
+        .add_attribute("count", transfers.len().to_string())
+        .add_attribute("fee_amount", total_fee)
+        .add_attribute("nfts_withdrawn", withdrawn.to_string())
+        .add_attribute("nfts_issued", issued.to_string());
+    Ok(resp)
+}
+
+// Records an address the first time a batch touches it, returns whether it is cw721 exempt
+fn _batch_account(
+    deps: &DepsMut,
+    token_info: &TokenInfo,
+    accounts: &mut BTreeMap<Addr, BatchAccount>,
+    address: &Addr,
+) -> StdResult<bool> {
+    if let Some(account) = accounts.get(address) {
+        return Ok(account.is_cw721_exempt);
+    }
+    let is_cw721_exempt = CW721_TRANSFER_EXEMPT
+        .may_load(deps.storage, address)?
+        .unwrap_or_default();
+    let balance = BALANCES
+        .may_load(deps.storage, address)?
+        .unwrap_or_default();
+    accounts.insert(
+        address.clone(),
+        BatchAccount {
+            is_cw721_exempt,
+            units_before: balance / token_info.units,
+            nfts_moved: 0,
+        },
+    );
+    Ok(is_cw721_exempt)
+}
+
+// Banks the NFTs of every net loser before retrieving or minting for the net gainers, returns
+// the number of NFTs withdrawn and issued
+fn _settle_batch(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    token_info: &TokenInfo,
+    accounts: &BTreeMap<Addr, BatchAccount>,
+) -> Result<(u64, u64), ContractError> {
+    let mut changes = vec![];
+    for (address, account) in accounts {
+        if account.is_cw721_exempt {
+            continue;
+        }
+        let balance = BALANCES
+            .may_load(deps.storage, address)?
+            .unwrap_or_default();
+        let units_after = balance / token_info.units;
+        let change =
+            units_after.u128() as i128 - account.units_before.u128() as i128 - account.nfts_moved;
+        changes.push((address, change));
+    }
+
+    let mut withdrawn = 0u64;
+    for (address, change) in changes.iter().filter(|(_, change)| *change < 0) {
+        for _i in 0..change.unsigned_abs() {
+            _withdraw_and_store_cw721(deps, env, info, address.to_string())?;
+            withdrawn += 1;
+        }
+    }
+    let mut issued = 0u64;
+    for (address, change) in changes.iter().filter(|(_, change)| *change > 0) {
+        for _i in 0..*change {
+            _retrieve_or_mint_cw721(deps, info, address.to_string())?;
+            issued += 1;
+        }
+    }
+
+    Ok((withdrawn, issued))
+}
+
+pub fn execute_enable_trading(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    launch_height: Option<u64>,
+    cooldown_blocks: u64,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let launch_height = launch_height.unwrap_or(env.block.height);
+    LAUNCH_INFO.update(deps.storage, |mut launch_info| -> StdResult<_> {
+        launch_info.trading_enabled = true;
+        launch_info.launch_height = Some(launch_height);
+        launch_info.cooldown_blocks = cooldown_blocks;
+        Ok(launch_info)
+    })?;
+
+    let resp = Response::new()
+        .add_attribute("action", "enable trading")
+        .add_attribute("launch_height", launch_height.to_string())
+        .add_attribute("cooldown_blocks", cooldown_blocks.to_string());
+    Ok(resp)
+}
+
+pub fn execute_set_cw721_transfer_exempt(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    state: bool,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    CW721_TRANSFER_EXEMPT.save(deps.storage, &address, &state)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set cw721 transfer exempt")
+        .add_attribute("address", address)
+        .add_attribute("state", state.to_string());
+    Ok(resp)
+}
+
+pub fn execute_set_fee_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    buy_fee_bps: u16,
+    sell_fee_bps: u16,
+    transfer_fee_bps: u16,
+    treasury: String,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    if buy_fee_bps > MAX_TRADE_FEE_BPS || sell_fee_bps > MAX_TRADE_FEE_BPS {
+        return Err(ContractError::FeeTooHigh {
+            max_bps: MAX_TRADE_FEE_BPS,
+        });
+    }
+    if transfer_fee_bps > MAX_TRANSFER_FEE_BPS {
+        return Err(ContractError::FeeTooHigh {
+            max_bps: MAX_TRANSFER_FEE_BPS,
+        });
+    }
+
+    let treasury = deps.api.addr_validate(&treasury)?;
+    FEE_CONFIG.save(
+        deps.storage,
+        &FeeConfig {
+            buy_fee_bps,
+            sell_fee_bps,
+            transfer_fee_bps,
+            treasury: treasury.clone(),
+        },
+    )?;
+    // Fees arrive in fractions, the treasury does not hold Cw721s
+    CW721_TRANSFER_EXEMPT.save(deps.storage, &treasury, &true)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set fee config")
+        .add_attribute("buy_fee_bps", buy_fee_bps.to_string())
+        .add_attribute("sell_fee_bps", sell_fee_bps.to_string())
+        .add_attribute("transfer_fee_bps", transfer_fee_bps.to_string())
+        .add_attribute("treasury", treasury);
+    Ok(resp)
+}
+
+pub fn execute_set_royalty_info(
+    deps: DepsMut,
+    info: MessageInfo,
+    royalty: Option<RoyaltyInfoMsg>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let resp = Response::new().add_attribute("action", "set royalty info");
+    match royalty {
+        Some(royalty) => {
+            let royalty_info = _validate_royalty_info(&deps, royalty)?;
+            ROYALTY_INFO.save(deps.storage, &royalty_info)?;
+            Ok(resp
+                .add_attribute("payment_address", royalty_info.payment_address)
+                .add_attribute(
+                    "royalty_percentage",
+                    royalty_info.royalty_percentage.to_string(),
+                ))
+        }
+        None => {
+            ROYALTY_INFO.remove(deps.storage);
+            Ok(resp)
+        }
+    }
+}
+
+pub fn execute_set_token_royalty_info(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    royalty: Option<RoyaltyInfoMsg>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    // Overrides can be set for any id up to the max nft supply, minted or not, or for any
+    // deposited id of a wrapped collection
+    _validate_token_id(&deps, &token_id)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set token royalty info")
+        .add_attribute("token_id", &token_id);
+    match royalty {
+        Some(royalty) => {
+            let royalty_info = _validate_royalty_info(&deps, royalty)?;
+            TOKEN_ROYALTY_INFO.save(deps.storage, &token_id, &royalty_info)?;
+            Ok(resp
+                .add_attribute("payment_address", royalty_info.payment_address)
+                .add_attribute(
+                    "royalty_percentage",
+                    royalty_info.royalty_percentage.to_string(),
+                ))
+        }
+        None => {
+            TOKEN_ROYALTY_INFO.remove(deps.storage, &token_id);
+            Ok(resp)
+        }
+    }
+}
+
+pub fn execute_set_nft_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    extension: Extension,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+    _assert_metadata_not_frozen(&deps)?;
+    _validate_token_id(&deps, &token_id)?;
+
+    // Live tokens are updated in place, banked or not yet minted tokens get it when minted
+    match NFT_TOKENS.may_load(deps.storage, &token_id)? {
+        Some(mut nft_token) => {
+            nft_token.extension = extension;
+            NFT_TOKENS.save(deps.storage, &token_id, &nft_token)?;
+        }
+        None => BANKED_EXTENSIONS.save(deps.storage, &token_id, &extension)?,
+    }
+
+    let resp = Response::new()
+        .add_attribute("action", "set nft metadata")
+        .add_attribute("token_id", token_id);
+    Ok(resp)
+}
+
+pub fn execute_commit_reveal(
+    deps: DepsMut,
+    info: MessageInfo,
+    seed_hash: Binary,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let mut reveal_info = REVEAL_INFO.load(deps.storage)?;
+    if reveal_info.revealed {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+    reveal_info.seed_hash = Some(seed_hash.clone());
+    REVEAL_INFO.save(deps.storage, &reveal_info)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "commit reveal")
+        .add_attribute("seed_hash", seed_hash.to_base64());
+    Ok(resp)
+}
+
+pub fn execute_reveal(
+    deps: DepsMut,
+    info: MessageInfo,
+    seed: Option<Binary>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let mut reveal_info = REVEAL_INFO.load(deps.storage)?;
+    if reveal_info.revealed {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+
+    // Open the committed seed, it derives the offset of the shuffled ids
+    if let Some(seed_hash) = &reveal_info.seed_hash {
+        let seed = seed.ok_or(ContractError::InvalidRevealSeed {})?;
+        if Sha256::digest(seed.as_slice()).as_slice() != seed_hash.as_slice() {
+            return Err(ContractError::InvalidRevealSeed {});
+        }
+        let max_nft_supply = MAX_NFT_SUPPLY.load(deps.storage)?;
+        let hash = Sha256::new()
+            .chain_update(seed.as_slice())
+            .chain_update(b"offset")
+            .finalize();
+        let random = u128::from_be_bytes(hash[0..16].try_into().unwrap());
+        reveal_info.offset = Some((random % max_nft_supply.u128().max(1)) as u64);
+        reveal_info.revealed_supply = Some(max_nft_supply);
+    }
+    reveal_info.revealed = true;
+    REVEAL_INFO.save(deps.storage, &reveal_info)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "reveal")
+        .add_attribute("offset", reveal_info.offset.unwrap_or_default().to_string());
+    Ok(resp)
+}
+
+pub fn execute_update_base_token_uri(
+    deps: DepsMut,
+    info: MessageInfo,
+    base_token_uri: Option<String>,
+    token_uri_template: Option<String>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+    _assert_metadata_not_frozen(&deps)?;
+
+    TOKEN_INFO.update(deps.storage, |mut token_info| -> StdResult<_> {
+        token_info.base_token_uri = base_token_uri.clone();
+        Ok(token_info)
+    })?;
+    let mut resp = Response::new()
+        .add_attribute("action", "update base token uri")
+        .add_attribute("base_token_uri", base_token_uri.unwrap_or_default());
+
+    if let Some(token_uri_template) = token_uri_template {
+        if !token_uri_template.contains("{id}") {
+            return Err(ContractError::InvalidTokenUriTemplate {});
+        }
+        METADATA_CONFIG.update(deps.storage, |mut metadata_config| -> StdResult<_> {
+            metadata_config.token_uri_template = token_uri_template.clone();
+            Ok(metadata_config)
+        })?;
+        resp = resp.add_attribute("token_uri_template", token_uri_template);
+    }
+
+    Ok(resp)
+}
+
+pub fn execute_set_token_uri(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    token_uri: Option<String>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+    _assert_metadata_not_frozen(&deps)?;
+    _validate_token_id(&deps, &token_id)?;
+
+    match &token_uri {
+        Some(token_uri) => TOKEN_URI_OVERRIDES.save(deps.storage, &token_id, token_uri)?,
+        None => TOKEN_URI_OVERRIDES.remove(deps.storage, &token_id),
+    }
+
+    let resp = Response::new()
+        .add_attribute("action", "set token uri")
+        .add_attribute("token_id", token_id)
+        .add_attribute("token_uri", token_uri.unwrap_or_default());
+    Ok(resp)
+}
+
+pub fn execute_freeze_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+    _assert_metadata_not_frozen(&deps)?;
+
+    // Freezing an unrevealed collection would leave it on the placeholder forever
+    if !REVEAL_INFO.load(deps.storage)?.revealed {
+        return Err(ContractError::NotRevealed {});
+    }
+    METADATA_CONFIG.update(deps.storage, |mut metadata_config| -> StdResult<_> {
+        metadata_config.frozen = true;
+        Ok(metadata_config)
+    })?;
+
+    Ok(Response::new().add_attribute("action", "freeze metadata"))
+}
+
+fn _assert_metadata_not_frozen(deps: &DepsMut) -> Result<(), ContractError> {
+    if METADATA_CONFIG.load(deps.storage)?.frozen {
+        return Err(ContractError::MetadataFrozen {});
+    }
+    Ok(())
+}
+
+pub fn execute_set_trait_layers(
+    deps: DepsMut,
+    info: MessageInfo,
+    layers: Vec<TraitLayer>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+    validate_trait_layers(&layers)?;
+
+    TRAIT_LAYERS.save(deps.storage, &layers)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set trait layers")
+        .add_attribute("layers", layers.len().to_string());
+    Ok(resp)
+}
+
+// Token ids run from 1 to the max nft supply. A wrapped collection keeps its own ids, only
+// those currently deposited exist.
+fn _validate_token_id(deps: &DepsMut, token_id: &str) -> Result<(), ContractError> {
+    if TOKEN_INFO.load(deps.storage)?.wrapped_collection.is_some() {
+        if !WRAPPED_NFTS.has(deps.storage, token_id) {
+            return Err(ContractError::InvalidTokenId {
+                token_id: token_id.to_string(),
+            });
+        }
+        return Ok(());
+    }
+    let max_nft_supply = MAX_NFT_SUPPLY.load(deps.storage)?;
+    match token_id.parse::<u128>() {
+        Ok(id) if id >= 1 && Uint128::from(id) <= max_nft_supply => Ok(()),
+        _ => Err(ContractError::InvalidTokenId {
+            token_id: token_id.to_string(),
+        }),
+    }
+}
+
+fn _validate_royalty_info(
+    deps: &DepsMut,
+    royalty: RoyaltyInfoMsg,
+) -> Result<RoyaltyInfo, ContractError> {
+    if royalty.royalty_percentage > 100 {
+        return Err(ContractError::InvalidRoyaltyPercentage {});
+    }
+    Ok(RoyaltyInfo {
+        payment_address: deps.api.addr_validate(&royalty.payment_address)?,
+        royalty_percentage: royalty.royalty_percentage,
+    })
+}
+
+fn _assert_admin(deps: &DepsMut, info: &MessageInfo) -> Result<(), ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    if token_info.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
 // Internal function for Cw-20 transfers. Also handles any Cw-721 transfers that may be required.
 fn _tranfer_cw20_with_cw721(
     mut deps: DepsMut,
@@ -42,22 +699,44 @@ fn _tranfer_cw20_with_cw721(
         .may_load(deps.storage, &recipient_address)?
         .unwrap_or_default();
 
-    // Transfer cw20 token here
-    let cw20_resp = _tranfer_cw20(&mut deps, &info, recipient.clone(), amount)?;
-    resp_attributes.extend(cw20_resp.attributes);
-
     // cw721 transfer exempt
-    let is_sender_cw721_exempt = CW721_TRANSFER_EXEMPT
-        .may_load(deps.storage, &info.sender)?
-        .unwrap_or_default();
+    let ctx = _transfer_context(&deps, &info.sender)?;
+    let is_sender_cw721_exempt = ctx.is_sender_cw721_exempt;
     let is_recipient_cw721_exempt = CW721_TRANSFER_EXEMPT
         .may_load(deps.storage, &recipient_address)?
         .unwrap_or_default();
 
+    // Trading gate and anti-bot cooldown
+    _check_launch(
+        &mut deps,
+        &env,
+        &ctx,
+        &recipient_address,
+        is_recipient_cw721_exempt,
+    )?;
+
+    let token_info = &ctx.token_info;
+
+    // Take the buy / sell / transfer fee first, so the recipient only receives the net amount
+    let (fee_amount, fee_resp) = _take_fee(
+        &mut deps,
+        &env,
+        &info,
+        &ctx,
+        &recipient_address,
+        is_recipient_cw721_exempt,
+        amount,
+    )?;
+    resp_attributes.extend(fee_resp.attributes);
+    let amount = amount.checked_sub(fee_amount)?;
+
+    // Transfer cw20 token here
+    let cw20_resp = _tranfer_cw20(&mut deps, &env, &info, recipient.clone(), amount)?;
+    resp_attributes.extend(cw20_resp.attributes);
+
     // cw20 balance after
     let cw20_balance_of_sender_after = BALANCES.load(deps.storage, &info.sender)?;
     let cw20_balance_of_recipient_after = BALANCES.load(deps.storage, &recipient_address)?;
-    let token_info = TOKEN_INFO.load(deps.storage)?;
 
     if is_sender_cw721_exempt && is_recipient_cw721_exempt {
         // Case 1) Both sender and recipient are Cw721 transfer exempt. No Cw721s need to be transferred.
@@ -81,8 +760,12 @@ fn _tranfer_cw20_with_cw721(
         let nft_to_withdraw_and_store = cw20_balance_of_sender_before / token_info.units
             - cw20_balance_of_sender_after / token_info.units;
         for _i in 0..nft_to_withdraw_and_store.u128() {
-            let res =
-                _withdraw_and_store_cw721(&mut deps, &info, info.clone().sender.into_string())?;
+            let res = _withdraw_and_store_cw721(
+                &mut deps,
+                &env,
+                &info,
+                info.clone().sender.into_string(),
+            )?;
             resp_attributes.extend(res.attributes);
         }
     } else {
@@ -95,7 +778,7 @@ fn _tranfer_cw20_with_cw721(
         //   b) If it causes the receiver to gain a whole new token that should be represented by an NFT
         //      due to receiving a fractional part that completes a whole token, retrieve or mint an NFT to the recevier.
         let nft_to_transfer = amount / token_info.units;
-        for i in 0..nft_to_transfer.u128() {
+        for _i in 0..nft_to_transfer.u128() {
             // Transfer nft here
         }
 
@@ -137,77 +820,1585 @@ fn _tranfer_cw20_with_cw721(
     Ok(resp)
 }
 
-fn _tranfer_cw20(
-    deps: &mut DepsMut,
-    info: &MessageInfo,
-    recipient: String,
-    amount: Uint128,
+pub fn execute_reconcile(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
 ) -> Result<Response, ContractError> {
-    let recipient = deps.api.addr_validate(&recipient)?;
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &recipient,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
-    )?;
+    let address = deps.api.addr_validate(&address)?;
+    let mut resp = Response::new()
+        .add_attribute("action", "reconcile")
+        .add_attribute("address", &address);
 
-    let res = Response::new()
-        .add_attribute("action", "transfer cw20")
-        .add_attribute("from", &info.sender)
-        .add_attribute("to", recipient)
-        .add_attribute("amount", amount);
+    // Exempt addresses do not hold NFTs for their balance
+    let is_cw721_exempt = CW721_TRANSFER_EXEMPT
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    if is_cw721_exempt {
+        return Ok(resp);
+    }
 
-    Ok(res)
-}
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let balance = BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let expected = (balance / token_info.units).u128();
+    let count = u128::from(
+        NFT_BALANCES
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default(),
+    );
 
-fn _retrieve_or_mint_cw721(
-    deps: &mut DepsMut,
-    info: &MessageInfo,
-    to: String,
-) -> Result<Response, ContractError> {
-    let token_id: Uint128;
+    if count < expected {
+        for _i in 0..(expected - count) {
+            let res = _retrieve_or_mint_cw721(&mut deps, &info, address.to_string())?;
+            resp = resp.add_attributes(res.attributes);
+        }
+    } else {
+        for _i in 0..(count - expected) {
+            let res = _withdraw_and_store_cw721(&mut deps, &env, &info, address.to_string())?;
+            resp = resp.add_attributes(res.attributes);
+        }
+    }
+
+    Ok(resp)
+}
+
+pub fn execute_lock_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let nft_token = NFT_TOKENS.load(deps.storage, &token_id)?;
+    if nft_token.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    LOCKED_NFTS.save(deps.storage, (&info.sender, &token_id), &Empty {})?;
+
+    let resp = Response::new()
+        .add_attribute("action", "lock nft")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id);
+    Ok(resp)
+}
+
+pub fn execute_unlock_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    if !LOCKED_NFTS.has(deps.storage, (&info.sender, &token_id)) {
+        return Err(ContractError::NftNotLocked { token_id });
+    }
+    LOCKED_NFTS.remove(deps.storage, (&info.sender, &token_id));
+
+    let resp = Response::new()
+        .add_attribute("action", "unlock nft")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id);
+    Ok(resp)
+}
+
+pub fn execute_set_nft_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    let resp = Response::new()
+        .add_attribute("action", "set nft order")
+        .add_attribute("owner", &info.sender);
+
+    if token_ids.is_empty() {
+        NFT_ORDER.remove(deps.storage, &info.sender);
+        return Ok(resp);
+    }
+
+    for (i, token_id) in token_ids.iter().enumerate() {
+        let nft_token = NFT_TOKENS.load(deps.storage, token_id)?;
+        if nft_token.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if token_ids[..i].contains(token_id) {
+            return Err(ContractError::DuplicateTokenId {
+                token_id: token_id.clone(),
+            });
+        }
+    }
+    NFT_ORDER.save(deps.storage, &info.sender, &token_ids)?;
+
+    Ok(resp.add_attribute("token_ids", token_ids.join(",")))
+}
+
+pub fn execute_set_nft_staking_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reward_denom: Denom,
+    reward_per_block: Uint128,
+    unbonding_period: Duration,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    // Checkpoint the index at the old rate, the new rate applies from this block on
+    let index = match NFT_STAKING_CONFIG.may_load(deps.storage)? {
+        Some(config) => NFT_REWARD_INDEX
+            .load(deps.storage)?
+            .current(config.reward_per_block, &env.block),
+        None => Uint128::zero(),
+    };
+    NFT_REWARD_INDEX.save(
+        deps.storage,
+        &RewardIndex {
+            index,
+            height: env.block.height,
+        },
+    )?;
+    let reward_denom = _validate_reward_denom(&deps, &env, reward_denom)?;
+    NFT_STAKING_CONFIG.save(
+        deps.storage,
+        &NftStakingConfig {
+            reward_denom,
+            reward_per_block,
+            unbonding_period,
+        },
+    )?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set nft staking config")
+        .add_attribute("reward_per_block", reward_per_block)
+        .add_attribute("unbonding_period", unbonding_period.to_string());
+    Ok(resp)
+}
+
+pub fn execute_stake_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let nft_token = NFT_TOKENS.load(deps.storage, &token_id)?;
+    if nft_token.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let stake = NFT_STAKES.may_load(deps.storage, (&info.sender, &token_id))?;
+    if stake.is_some_and(|stake| stake.in_custody(&env.block)) {
+        return Err(ContractError::NftStaked { token_id });
+    }
+
+    let reward_index = _nft_reward_index(&deps, &env)?;
+    NFT_STAKES.save(
+        deps.storage,
+        (&info.sender, &token_id),
+        &NftStake {
+            reward_index,
+            unbonding: None,
+        },
+    )?;
+
+    let resp = Response::new()
+        .add_attribute("action", "stake nft")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id);
+    Ok(resp)
+}
+
+pub fn execute_unstake_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let mut stake = match NFT_STAKES.may_load(deps.storage, (&info.sender, &token_id))? {
+        Some(stake) if stake.unbonding.is_none() => stake,
+        _ => return Err(ContractError::NftNotStaked { token_id }),
+    };
+
+    let reward_index = _nft_reward_index(&deps, &env)?;
+    let pending_rewards = stake.pending_rewards(reward_index);
+    NFT_STAKING_REWARDS.update(deps.storage, &info.sender, |rewards| -> StdResult<_> {
+        Ok(rewards.unwrap_or_default() + pending_rewards)
+    })?;
+
+    let config = NFT_STAKING_CONFIG.load(deps.storage)?;
+    let unbonding = config.unbonding_period.after(&env.block);
+    stake.reward_index = reward_index;
+    stake.unbonding = Some(unbonding);
+    NFT_STAKES.save(deps.storage, (&info.sender, &token_id), &stake)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "unstake nft")
+        .add_attribute("owner", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("unbonding", unbonding.to_string());
+    Ok(resp)
+}
+
+pub fn execute_claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let reward_index = _nft_reward_index(&deps, &env)?;
+    let mut rewards = NFT_STAKING_REWARDS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+
+    // Accrue the staked NFTs and drop the unbonded ones
+    let stakes = NFT_STAKES
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (token_id, mut stake) in stakes {
+        if !stake.in_custody(&env.block) {
+            NFT_STAKES.remove(deps.storage, (&info.sender, &token_id));
+        } else if stake.unbonding.is_none() {
+            rewards += stake.pending_rewards(reward_index);
+            stake.reward_index = reward_index;
+            NFT_STAKES.save(deps.storage, (&info.sender, &token_id), &stake)?;
+        }
+    }
+    if rewards.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    NFT_STAKING_REWARDS.remove(deps.storage, &info.sender);
+
+    let config = NFT_STAKING_CONFIG.load(deps.storage)?;
+    let resp = Response::new()
+        .add_message(_reward_msg(&config.reward_denom, &info.sender, rewards)?)
+        .add_attribute("action", "claim rewards")
+        .add_attribute("owner", info.sender)
+        .add_attribute("amount", rewards);
+    Ok(resp)
+}
+
+pub fn execute_set_fraction_staking_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reward_denom: Denom,
+    reward_per_block: Uint128,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    // Checkpoint the index at the old rate, the new rate applies from this block on
+    let reward_index = match FRACTION_STAKING_CONFIG.may_load(deps.storage)? {
+        Some(config) => {
+            let reward_index = FRACTION_REWARD_INDEX.load(deps.storage)?;
+            FractionRewardIndex {
+                index: reward_index.current(config.reward_per_block, &env.block),
+                height: env.block.height,
+                total_staked: reward_index.total_staked,
+            }
+        }
+        None => FractionRewardIndex {
+            index: Decimal::zero(),
+            height: env.block.height,
+            total_staked: Uint128::zero(),
+        },
+    };
+    FRACTION_REWARD_INDEX.save(deps.storage, &reward_index)?;
+    let reward_denom = _validate_reward_denom(&deps, &env, reward_denom)?;
+    FRACTION_STAKING_CONFIG.save(
+        deps.storage,
+        &FractionStakingConfig {
+            reward_denom,
+            reward_per_block,
+        },
+    )?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set fraction staking config")
+        .add_attribute("reward_per_block", reward_per_block);
+    Ok(resp)
+}
+
+pub fn execute_stake_fractions(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let mut reward_index = _fraction_reward_index(&deps, &env)?;
+    let mut stake = FRACTION_STAKES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or(FractionStake {
+            amount: Uint128::zero(),
+            reward_index: reward_index.index,
+            pending_rewards: Uint128::zero(),
+        });
+    stake.accrue(reward_index.index);
+    stake.amount += amount;
+    FRACTION_STAKES.save(deps.storage, &info.sender, &stake)?;
+    reward_index.total_staked += amount;
+    FRACTION_REWARD_INDEX.save(deps.storage, &reward_index)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "stake fractions")
+        .add_attribute("staker", &info.sender)
+        .add_attribute("amount", amount);
+
+    let res = _transfer_to_contract(&mut deps, &env, &info, amount)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_unstake_fractions(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let mut reward_index = _fraction_reward_index(&deps, &env)?;
+    let mut stake = FRACTION_STAKES
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::InsufficientStake {
+            staked: Uint128::zero(),
+        })?;
+    if stake.amount < amount {
+        return Err(ContractError::InsufficientStake {
+            staked: stake.amount,
+        });
+    }
+    stake.accrue(reward_index.index);
+    stake.amount -= amount;
+    if stake.amount.is_zero() && stake.pending_rewards.is_zero() {
+        FRACTION_STAKES.remove(deps.storage, &info.sender);
+    } else {
+        FRACTION_STAKES.save(deps.storage, &info.sender, &stake)?;
+    }
+    reward_index.total_staked -= amount;
+    FRACTION_REWARD_INDEX.save(deps.storage, &reward_index)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "unstake fractions")
+        .add_attribute("staker", &info.sender)
+        .add_attribute("amount", amount);
+
+    let res = _transfer_from_contract(&mut deps, &env, &info, info.sender.to_string(), amount)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_claim_fraction_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let reward_index = _fraction_reward_index(&deps, &env)?;
+    FRACTION_REWARD_INDEX.save(deps.storage, &reward_index)?;
+    let mut stake = FRACTION_STAKES
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NothingToClaim {})?;
+    stake.accrue(reward_index.index);
+    let rewards = stake.pending_rewards;
+    if rewards.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    stake.pending_rewards = Uint128::zero();
+    if stake.amount.is_zero() {
+        FRACTION_STAKES.remove(deps.storage, &info.sender);
+    } else {
+        FRACTION_STAKES.save(deps.storage, &info.sender, &stake)?;
+    }
+
+    let config = FRACTION_STAKING_CONFIG.load(deps.storage)?;
+    let resp = Response::new()
+        .add_message(_reward_msg(&config.reward_denom, &info.sender, rewards)?)
+        .add_attribute("action", "claim fraction rewards")
+        .add_attribute("staker", info.sender)
+        .add_attribute("amount", rewards);
+    Ok(resp)
+}
+
+pub fn execute_set_sale_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    mut sale_config: SaleConfig,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+    _assert_mintable(&deps)?;
+
+    if !is_valid_pricing(&sale_config.pricing) || sale_config.start_time >= sale_config.end_time {
+        return Err(ContractError::InvalidSaleConfig {});
+    }
+    // A new config keeps the progress of the current sale. Proceeds are paid out in the sale's
+    // denom, so it can only change once they are withdrawn.
+    if let Some(current) = SALE_CONFIG.may_load(deps.storage)? {
+        if !current.proceeds.is_zero() && current.denom != sale_config.denom {
+            return Err(ContractError::InvalidSaleConfig {});
+        }
+        sale_config.sold = current.sold;
+        sale_config.proceeds = current.proceeds;
+    }
+    SALE_CONFIG.save(deps.storage, &sale_config)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set sale config")
+        .add_attribute("denom", sale_config.denom)
+        .add_attribute("total_cap", sale_config.total_cap);
+    Ok(resp)
+}
+
+pub fn execute_buy_with_native(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut sale_config = SALE_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::SaleNotConfigured {})?;
+    if !sale_config.is_active(&env.block) {
+        return Err(ContractError::SaleNotActive {});
+    }
+    let paid = must_pay(&info, &sale_config.denom)?;
+
+    // Buy the requested amount, or what the payment covers, within the wallet and total caps
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let purchased = SALE_PURCHASES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let remaining = sale_config
+        .per_wallet_cap
+        .saturating_sub(purchased)
+        .min(sale_config.total_cap.saturating_sub(sale_config.sold));
+    if remaining.is_zero() {
+        return Err(ContractError::SaleCapReached {});
+    }
+    let amount = match amount {
+        Some(amount) => amount.min(remaining),
+        None => {
+            let amount = max_affordable_amount(
+                &sale_config.pricing,
+                env.block.height,
+                sale_config.sold,
+                token_info.units,
+                paid,
+                remaining,
+            );
+            if amount.is_zero() {
+                return Err(ContractError::InsufficientPayment {});
+            }
+            amount
+        }
+    };
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let cost = sale_cost(
+        &sale_config.pricing,
+        env.block.height,
+        sale_config.sold,
+        token_info.units,
+        amount,
+    )?;
+    if paid < cost {
+        return Err(ContractError::InsufficientPayment {});
+    }
+    let refund = paid - cost;
+
+    SALE_PURCHASES.save(deps.storage, &info.sender, &(purchased + amount))?;
+    sale_config.sold += amount;
+    sale_config.proceeds += cost;
+    SALE_CONFIG.save(deps.storage, &sale_config)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "buy with native")
+        .add_attribute("buyer", &info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("cost", cost);
+    if !refund.is_zero() {
+        resp = resp.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin::new(refund.u128(), &sale_config.denom)],
+        });
+    }
+
+    let res = _mint_cw20(&mut deps, &env, &info, info.sender.to_string(), amount)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_withdraw_proceeds(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender,
+    };
+
+    // Collect the proceeds of the sale and of every whitelist phase
+    let mut proceeds: Vec<Coin> = vec![];
+    if let Some(mut sale_config) = SALE_CONFIG.may_load(deps.storage)? {
+        _add_coin(&mut proceeds, &sale_config.denom, sale_config.proceeds);
+        sale_config.proceeds = Uint128::zero();
+        SALE_CONFIG.save(deps.storage, &sale_config)?;
+    }
+    let phases = WHITELIST_PHASES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (phase_id, mut phase) in phases {
+        if let Some(price) = &phase.price_per_unit {
+            _add_coin(&mut proceeds, &price.denom, phase.proceeds);
+            phase.proceeds = Uint128::zero();
+            WHITELIST_PHASES.save(deps.storage, phase_id, &phase)?;
+        }
+    }
+    if proceeds.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let amount = proceeds
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let resp = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: proceeds,
+        })
+        .add_attribute("action", "withdraw proceeds")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount);
+    Ok(resp)
+}
+
+pub fn execute_set_whitelist_phase(
+    deps: DepsMut,
+    info: MessageInfo,
+    phase_id: u64,
+    mut phase: WhitelistPhase,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+    _assert_mintable(&deps)?;
+
+    if phase.merkle_root.len() != 32 {
+        return Err(ContractError::InvalidMerkleRoot {});
+    }
+    if phase.start_time >= phase.end_time
+        || phase
+            .price_per_unit
+            .as_ref()
+            .is_some_and(|price| price.amount.is_zero())
+    {
+        return Err(ContractError::InvalidWhitelistPhase {});
+    }
+    // Proceeds stay with the phase until withdrawn, so its denom can only change once they are
+    phase.proceeds = Uint128::zero();
+    if let Some(current) = WHITELIST_PHASES.may_load(deps.storage, phase_id)? {
+        if !current.proceeds.is_zero() {
+            let denom = |phase: &WhitelistPhase| phase.price_per_unit.clone().map(|p| p.denom);
+            if denom(&current) != denom(&phase) {
+                return Err(ContractError::InvalidWhitelistPhase {});
+            }
+            phase.proceeds = current.proceeds;
+        }
+    }
+    WHITELIST_PHASES.save(deps.storage, phase_id, &phase)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "set whitelist phase")
+        .add_attribute("phase_id", phase_id.to_string())
+        .add_attribute("merkle_root", phase.merkle_root.to_base64());
+    Ok(resp)
+}
+
+pub fn execute_claim_airdrop(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    proof: Vec<Binary>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let (phase_id, _, claimed) = _find_whitelist_phase(&deps, &env, &info, false, amount, &proof)?;
+    if !claimed.is_zero() {
+        return Err(ContractError::AllocationClaimed {});
+    }
+    WHITELIST_CLAIMED.save(deps.storage, (phase_id, &info.sender), &amount)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "claim airdrop")
+        .add_attribute("phase_id", phase_id.to_string())
+        .add_attribute("claimer", &info.sender)
+        .add_attribute("amount", amount);
+    let res = _mint_cw20(&mut deps, &env, &info, info.sender.to_string(), amount)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_whitelist_buy(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    proof: Vec<Binary>,
+) -> Result<Response, ContractError> {
+    let (phase_id, mut phase, claimed) =
+        _find_whitelist_phase(&deps, &env, &info, true, amount, &proof)?;
+    let remaining = amount.saturating_sub(claimed);
+    if remaining.is_zero() {
+        return Err(ContractError::AllocationClaimed {});
+    }
+    let price = phase.price_per_unit.clone().unwrap();
+    let paid = must_pay(&info, &price.denom)?;
+
+    // Buy as much as the payment allows within the allocation
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let bought = paid
+        .multiply_ratio(token_info.units, price.amount)
+        .min(remaining);
+    if bought.is_zero() {
+        return Err(ContractError::InsufficientPayment {});
+    }
+    let cost = bought.checked_mul_ceil((price.amount, token_info.units))?;
+    let refund = paid - cost;
+
+    WHITELIST_CLAIMED.save(deps.storage, (phase_id, &info.sender), &(claimed + bought))?;
+    phase.proceeds += cost;
+    WHITELIST_PHASES.save(deps.storage, phase_id, &phase)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "whitelist buy")
+        .add_attribute("phase_id", phase_id.to_string())
+        .add_attribute("buyer", &info.sender)
+        .add_attribute("amount", bought)
+        .add_attribute("cost", cost);
+    if !refund.is_zero() {
+        resp = resp.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin::new(refund.u128(), price.denom)],
+        });
+    }
+    let res = _mint_cw20(&mut deps, &env, &info, info.sender.to_string(), bought)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+// Finds the first active phase of the given kind whose root proves the caller's allocation,
+// preferring one where the allocation is not used up yet
+fn _find_whitelist_phase(
+    deps: &DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    priced: bool,
+    allocation: Uint128,
+    proof: &[Binary],
+) -> Result<(u64, WhitelistPhase, Uint128), ContractError> {
+    let leaf = merkle_leaf(&info.sender, allocation);
+    let mut used_up = None;
+    for item in WHITELIST_PHASES.range(deps.storage, None, None, Order::Ascending) {
+        let (phase_id, phase) = item?;
+        if !phase.is_active(&env.block)
+            || phase.price_per_unit.is_some() != priced
+            || !verify_merkle_proof(&phase.merkle_root, leaf, proof)
+        {
+            continue;
+        }
+        let claimed = WHITELIST_CLAIMED
+            .may_load(deps.storage, (phase_id, &info.sender))?
+            .unwrap_or_default();
+        if claimed < allocation {
+            return Ok((phase_id, phase, claimed));
+        }
+        used_up.get_or_insert((phase_id, phase, claimed));
+    }
+    used_up.ok_or(ContractError::InvalidMerkleProof {})
+}
+
+fn _add_coin(coins: &mut Vec<Coin>, denom: &str, amount: Uint128) {
+    if amount.is_zero() {
+        return;
+    }
+    match coins.iter_mut().find(|coin| coin.denom == denom) {
+        Some(coin) => coin.amount += amount,
+        None => coins.push(Coin::new(amount.u128(), denom)),
+    }
+}
+
+pub fn execute_create_vesting_schedule(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    schedule: VestingScheduleMsg,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let beneficiary = deps.api.addr_validate(&schedule.beneficiary)?;
+    if VESTING_SCHEDULES.has(deps.storage, &beneficiary) {
+        return Err(ContractError::VestingScheduleExists {
+            beneficiary: beneficiary.to_string(),
+        });
+    }
+    let vesting_schedule = VestingSchedule {
+        total: schedule.amount,
+        claimed: Uint128::zero(),
+        start_height: env.block.height,
+        start_time: env.block.time,
+        cliff: schedule.cliff,
+        duration: schedule.duration,
+    };
+    if !vesting_schedule.is_valid() {
+        return Err(ContractError::InvalidVestingSchedule {});
+    }
+    VESTING_SCHEDULES.save(deps.storage, &beneficiary, &vesting_schedule)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "create vesting schedule")
+        .add_attribute("beneficiary", &beneficiary)
+        .add_attribute("amount", schedule.amount);
+    let res = _transfer_to_contract(&mut deps, &env, &info, schedule.amount)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_claim_vested(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut schedule = VESTING_SCHEDULES
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NothingToClaim {})?;
+    let claimable = schedule.vested(&env.block) - schedule.claimed;
+    if claimable.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    schedule.claimed += claimable;
+    if schedule.claimed == schedule.total {
+        VESTING_SCHEDULES.remove(deps.storage, &info.sender);
+    } else {
+        VESTING_SCHEDULES.save(deps.storage, &info.sender, &schedule)?;
+    }
+
+    let mut resp = Response::new()
+        .add_attribute("action", "claim vested")
+        .add_attribute("beneficiary", &info.sender)
+        .add_attribute("amount", claimable);
+    let res = _transfer_from_contract(&mut deps, &env, &info, info.sender.to_string(), claimable)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_set_reroll_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee: Option<RerollFee>,
+) -> Result<Response, ContractError> {
+    _assert_admin(&deps, &info)?;
+
+    let resp = Response::new().add_attribute("action", "set reroll fee");
+    match fee {
+        Some(fee) => {
+            let resp = match &fee {
+                RerollFee::Native(coin) => resp.add_attribute("fee", coin.to_string()),
+                RerollFee::Fractions(amount) => {
+                    // A whole unit would take an NFT away from the caller
+                    let token_info = TOKEN_INFO.load(deps.storage)?;
+                    if *amount >= token_info.units {
+                        return Err(ContractError::InvalidRerollFee {});
+                    }
+                    resp.add_attribute("fee", amount.to_string())
+                }
+            };
+            REROLL_FEE.save(deps.storage, &fee)?;
+            Ok(resp)
+        }
+        None => {
+            REROLL_FEE.remove(deps.storage);
+            Ok(resp)
+        }
+    }
+}
+
+pub fn execute_reroll(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let nft_token = NFT_TOKENS.load(deps.storage, &token_id)?;
+    if nft_token.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if LOCKED_NFTS.has(deps.storage, (&info.sender, &token_id)) {
+        return Err(ContractError::NftLocked { token_id });
+    }
+    if _is_nft_protected(&deps, &env, &info.sender, &token_id)? {
+        return Err(ContractError::NftStaked { token_id });
+    }
+
+    let mut resp = Response::new()
+        .add_attribute("action", "reroll")
+        .add_attribute("token_id", &token_id);
+
+    let fee_config = FEE_CONFIG.load(deps.storage)?;
+    match REROLL_FEE.may_load(deps.storage)? {
+        Some(RerollFee::Native(coin)) => {
+            let paid = must_pay(&info, &coin.denom)?;
+            if paid < coin.amount {
+                return Err(ContractError::InsufficientRerollFee {
+                    amount: coin.amount,
+                });
+            }
+            // Exactly the fee goes to the treasury, the overpayment goes back to the caller
+            let refund = paid - coin.amount;
+            resp = resp
+                .add_message(BankMsg::Send {
+                    to_address: fee_config.treasury.to_string(),
+                    amount: vec![coin.clone()],
+                })
+                .add_attribute("fee_amount", coin.amount);
+            if !refund.is_zero() {
+                resp = resp.add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: vec![Coin::new(refund.u128(), coin.denom)],
+                });
+            }
+        }
+        Some(RerollFee::Fractions(amount)) => {
+            nonpayable(&info)?;
+            // The fee must not cost a whole unit, or the caller's NFTs would fall out of sync
+            let token_info = TOKEN_INFO.load(deps.storage)?;
+            let balance = BALANCES
+                .may_load(deps.storage, &info.sender)?
+                .unwrap_or_default();
+            let balance_after = balance.checked_sub(amount)?;
+            if balance_after / token_info.units != balance / token_info.units {
+                return Err(ContractError::InsufficientFractions {});
+            }
+            _tranfer_cw20(
+                &mut deps,
+                &env,
+                &info,
+                fee_config.treasury.to_string(),
+                amount,
+            )?;
+            resp = resp.add_attribute("fee_amount", amount);
+        }
+        None => {
+            nonpayable(&info)?;
+        }
+    }
+
+    // Hand out the next NFT before banking the given one, so it can never come straight back
+    let is_deque_empty = DEQUE_NFT.is_empty(deps.storage)?;
+    let nft_count = NFT_COUNT.load(deps.storage)?;
+    let max_nft_supply = MAX_NFT_SUPPLY.load(deps.storage)?;
+    if is_deque_empty && Uint128::from(nft_count) >= max_nft_supply {
+        return Err(ContractError::NothingToReroll {});
+    }
+    let res = _retrieve_or_mint_cw721(&mut deps, &info, info.sender.to_string())?;
+    resp = resp.add_attributes(res.attributes);
+
+    DEQUE_NFT.push_front(deps.storage, &token_id)?;
+    let res = _burn_nft(&mut deps, &info, token_id)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_deposit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let amount = match token_info.underlying {
+        Some(Denom::Native(denom)) => must_pay(&info, &denom)?,
+        Some(Denom::Cw20(_)) => return Err(ContractError::InvalidUnderlying {}),
+        None => return Err(ContractError::NotWrapper {}),
+    };
+
+    let mut resp = Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("sender", &info.sender)
+        .add_attribute("amount", amount);
+    let res = _mint_cw20(&mut deps, &env, &info, info.sender.to_string(), amount)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_receive(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    match token_info.underlying {
+        Some(Denom::Cw20(address)) if address == info.sender => {}
+        Some(_) => return Err(ContractError::InvalidUnderlying {}),
+        None => return Err(ContractError::NotWrapper {}),
+    }
+    if msg.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // The depositor is the sender of the cw20 tokens, not the token contract
+    let depositor = deps.api.addr_validate(&msg.sender)?;
+    let depositor_info = MessageInfo {
+        sender: depositor.clone(),
+        funds: vec![],
+    };
+    let mut resp = Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("sender", &depositor)
+        .add_attribute("amount", msg.amount);
+    let res = _mint_cw20(
+        &mut deps,
+        &env,
+        &depositor_info,
+        depositor.to_string(),
+        msg.amount,
+    )?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_withdraw(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let underlying = token_info.underlying.ok_or(ContractError::NotWrapper {})?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut resp = Response::new()
+        .add_attribute("action", "withdraw")
+        .add_attribute("sender", &info.sender)
+        .add_attribute("amount", amount);
+    let res = _burn_cw20(&mut deps, &env, &info, amount)?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp.add_message(_reward_msg(&underlying, &info.sender, amount)?))
+}
+
+pub fn execute_receive_nft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    match token_info.wrapped_collection {
+        Some(collection) if collection == info.sender => {}
+        Some(_) => return Err(ContractError::InvalidUnderlying {}),
+        None => return Err(ContractError::NotWrapper {}),
+    }
+    // The mirror copies the uri and metadata of the original token. Once metadata is frozen,
+    // a copy kept from an earlier deposit of the same id is not replaced.
+    let is_frozen = METADATA_CONFIG.load(deps.storage)?.frozen;
+    let has_copy = TOKEN_URI_OVERRIDES.has(deps.storage, &msg.token_id)
+        || BANKED_EXTENSIONS.has(deps.storage, &msg.token_id);
+    if !is_frozen || !has_copy {
+        let original: WrappedNftInfo = deps.querier.query_wasm_smart(
+            &info.sender,
+            &Cw721QueryMsg::NftInfo {
+                token_id: msg.token_id.clone(),
+            },
+        )?;
+        match original.token_uri {
+            Some(token_uri) => TOKEN_URI_OVERRIDES.save(deps.storage, &msg.token_id, &token_uri)?,
+            None => TOKEN_URI_OVERRIDES.remove(deps.storage, &msg.token_id),
+        }
+        match original
+            .extension
+            .map(Metadata::from)
+            .filter(|metadata| *metadata != Metadata::default())
+        {
+            Some(metadata) => {
+                BANKED_EXTENSIONS.save(deps.storage, &msg.token_id, &Some(metadata))?
+            }
+            None => BANKED_EXTENSIONS.remove(deps.storage, &msg.token_id),
+        }
+    }
+
+    // Queue the original id to be handed out next, so the depositor gets it unless exempt
+    WRAPPED_NFTS.save(deps.storage, &msg.token_id, &Empty {})?;
+    DEQUE_NFT.push_back(deps.storage, &msg.token_id)?;
+    let depositor = deps.api.addr_validate(&msg.sender)?;
+    let depositor_info = MessageInfo {
+        sender: depositor.clone(),
+        funds: vec![],
+    };
+    let mut resp = Response::new()
+        .add_attribute("action", "wrap nft")
+        .add_attribute("sender", &depositor)
+        .add_attribute("token_id", &msg.token_id);
+    let res = _mint_cw20(
+        &mut deps,
+        &env,
+        &depositor_info,
+        depositor.to_string(),
+        token_info.units,
+    )?;
+    resp = resp.add_attributes(res.attributes);
+
+    Ok(resp)
+}
+
+pub fn execute_redeem_nft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let collection = token_info
+        .wrapped_collection
+        .ok_or(ContractError::NotWrapper {})?;
+    let nft_token = NFT_TOKENS.load(deps.storage, &token_id)?;
+    if nft_token.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if LOCKED_NFTS.has(deps.storage, (&info.sender, &token_id)) {
+        return Err(ContractError::NftLocked { token_id });
+    }
+    if _is_nft_protected(&deps, &env, &info.sender, &token_id)? {
+        return Err(ContractError::NftStaked { token_id });
+    }
+
+    // The mirror leaves with the unit backing it, it is not banked
+    _burn_balance(&mut deps, &env, &info.sender, token_info.units)?;
+    let res = _burn_nft(&mut deps, &info, token_id.clone())?;
+    WRAPPED_NFTS.remove(deps.storage, &token_id);
+    // Once metadata is frozen, the copy stays for when the token is deposited again
+    if !METADATA_CONFIG.load(deps.storage)?.frozen {
+        BANKED_EXTENSIONS.remove(deps.storage, &token_id);
+        TOKEN_URI_OVERRIDES.remove(deps.storage, &token_id);
+    }
+
+    let resp = Response::new()
+        .add_attribute("action", "redeem nft")
+        .add_attribute("sender", &info.sender)
+        .add_attributes(res.attributes)
+        .add_message(WasmMsg::Execute {
+            contract_addr: collection.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: info.sender.to_string(),
+                token_id,
+            })?,
+            funds: vec![],
+        });
+    Ok(resp)
+}
+
+// The supply of a wrapper must stay backed by deposits
+fn _assert_mintable(deps: &DepsMut) -> Result<(), ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    if token_info.underlying.is_some() || token_info.wrapped_collection.is_some() {
+        return Err(ContractError::UnbackedSupply {});
+    }
+    Ok(())
+}
+
+fn _nft_reward_index(deps: &DepsMut, env: &Env) -> Result<Uint128, ContractError> {
+    let config = NFT_STAKING_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::StakingNotConfigured {})?;
+    let reward_index = NFT_REWARD_INDEX.load(deps.storage)?;
+    Ok(reward_index.current(config.reward_per_block, &env.block))
+}
+
+// Brings the fraction reward index up to the current block, callers save it back
+fn _fraction_reward_index(deps: &DepsMut, env: &Env) -> Result<FractionRewardIndex, ContractError> {
+    let config = FRACTION_STAKING_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::FractionStakingNotConfigured {})?;
+    let reward_index = FRACTION_REWARD_INDEX.load(deps.storage)?;
+    Ok(FractionRewardIndex {
+        index: reward_index.current(config.reward_per_block, &env.block),
+        height: env.block.height,
+        total_staked: reward_index.total_staked,
+    })
+}
+
+// Rewards are paid from the contract's own holdings, so they can not be its staked fractions,
+// the deposits backing a wrapper, nor the proceeds of a sale or phase that is running or not
+// withdrawn yet
+fn _validate_reward_denom(
+    deps: &DepsMut,
+    env: &Env,
+    reward_denom: Denom,
+) -> Result<Denom, ContractError> {
+    let reward_denom = match reward_denom {
+        Denom::Cw20(address) => {
+            let address = deps.api.addr_validate(address.as_str())?;
+            if address == env.contract.address {
+                return Err(ContractError::InvalidRewardDenom {});
+            }
+            Denom::Cw20(address)
+        }
+        denom => denom,
+    };
+
+    let mut reserved = vec![];
+    if let Some(underlying) = TOKEN_INFO.load(deps.storage)?.underlying {
+        reserved.push(underlying);
+    }
+    if let Some(sale_config) = SALE_CONFIG.may_load(deps.storage)? {
+        if env.block.time < sale_config.end_time || !sale_config.proceeds.is_zero() {
+            reserved.push(Denom::Native(sale_config.denom));
+        }
+    }
+    for phase in WHITELIST_PHASES.range(deps.storage, None, None, Order::Ascending) {
+        let (_, phase) = phase?;
+        if let Some(price) = phase.price_per_unit {
+            if env.block.time < phase.end_time || !phase.proceeds.is_zero() {
+                reserved.push(Denom::Native(price.denom));
+            }
+        }
+    }
+    if reserved.contains(&reward_denom) {
+        return Err(ContractError::InvalidRewardDenom {});
+    }
+    Ok(reward_denom)
+}
+
+fn _staked_nft_count(deps: &DepsMut, env: &Env, owner: &Addr) -> StdResult<u64> {
+    let mut staked = 0u64;
+    for stake in NFT_STAKES
+        .prefix(owner)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, stake) = stake?;
+        if stake.in_custody(&env.block) {
+            staked += 1;
+        }
+    }
+    Ok(staked)
+}
+
+// Locked and staked NFTs can not be banked by fractional transfers
+fn _is_nft_protected(deps: &DepsMut, env: &Env, owner: &Addr, token_id: &str) -> StdResult<bool> {
+    if LOCKED_NFTS.has(deps.storage, (owner, token_id)) {
+        return Ok(true);
+    }
+    let stake = NFT_STAKES.may_load(deps.storage, (owner, token_id))?;
+    Ok(stake.is_some_and(|stake| stake.in_custody(&env.block)))
+}
+
+fn _reward_msg(denom: &Denom, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin::new(amount.u128(), denom)],
+        }
+        .into(),
+        Denom::Cw20(address) => WasmMsg::Execute {
+            contract_addr: address.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+    Ok(msg)
+}
+
+// Configs read by every transfer of a message and the sender's exemption, loaded once
+struct TransferContext {
+    token_info: TokenInfo,
+    fee_config: FeeConfig,
+    launch_info: LaunchInfo,
+    is_sender_cw721_exempt: bool,
+}
+
+fn _transfer_context(deps: &DepsMut, sender: &Addr) -> StdResult<TransferContext> {
+    Ok(TransferContext {
+        token_info: TOKEN_INFO.load(deps.storage)?,
+        fee_config: FEE_CONFIG.load(deps.storage)?,
+        launch_info: LAUNCH_INFO.load(deps.storage)?,
+        is_sender_cw721_exempt: CW721_TRANSFER_EXEMPT
+            .may_load(deps.storage, sender)?
+            .unwrap_or_default(),
+    })
+}
+
+// Before launch only exempt addresses can transfer. During the cooldown window after launch,
+// a non exempt recipient may receive at most once per block.
+fn _check_launch(
+    deps: &mut DepsMut,
+    env: &Env,
+    ctx: &TransferContext,
+    recipient: &Addr,
+    is_recipient_cw721_exempt: bool,
+) -> Result<(), ContractError> {
+    let launch_info = &ctx.launch_info;
+    if !launch_info.is_launched(&env.block) {
+        if !ctx.is_sender_cw721_exempt {
+            return Err(ContractError::TradingNotEnabled {});
+        }
+        return Ok(());
+    }
+
+    if !is_recipient_cw721_exempt && launch_info.in_cooldown(&env.block) {
+        let last_height = LAST_TRANSFER_HEIGHT.may_load(deps.storage, recipient)?;
+        if last_height == Some(env.block.height) {
+            return Err(ContractError::TransferCooldown {
+                address: recipient.to_string(),
+            });
+        }
+        LAST_TRANSFER_HEIGHT.save(deps.storage, recipient, &env.block.height)?;
+    }
+
+    Ok(())
+}
+
+// Deduct the fee from a transfer and send it to the treasury. Buy is a transfer from an exempt
+// address (e.g. an AMM pair), sell is a transfer to one. Transfers from or to the admin or the
+// treasury are not taxed. Returns the fee amount taken.
+fn _take_fee(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    ctx: &TransferContext,
+    recipient: &Addr,
+    is_recipient_cw721_exempt: bool,
+    amount: Uint128,
+) -> Result<(Uint128, Response), ContractError> {
+    let (token_info, fee_config) = (&ctx.token_info, &ctx.fee_config);
+    let is_sender_cw721_exempt = ctx.is_sender_cw721_exempt;
+    let is_fee_free = [&token_info.admin, &fee_config.treasury]
+        .iter()
+        .any(|addr| **addr == info.sender || *addr == recipient);
+
+    if is_fee_free || (is_sender_cw721_exempt && is_recipient_cw721_exempt) {
+        return Ok((Uint128::zero(), Response::new()));
+    }
+
+    let (fee_type, fee_bps) = if is_sender_cw721_exempt {
+        ("buy", fee_config.buy_fee_bps)
+    } else if is_recipient_cw721_exempt {
+        ("sell", fee_config.sell_fee_bps)
+    } else {
+        ("transfer", fee_config.transfer_fee_bps)
+    };
+
+    let fee_amount = amount.multiply_ratio(fee_bps, BPS_DENOMINATOR);
+    if fee_amount.is_zero() {
+        return Ok((fee_amount, Response::new()));
+    }
+    _tranfer_cw20(deps, env, info, fee_config.treasury.to_string(), fee_amount)?;
+
+    let resp = Response::new()
+        .add_attribute("fee_type", fee_type)
+        .add_attribute("fee_amount", fee_amount)
+        .add_attribute("treasury", &fee_config.treasury);
+    Ok((fee_amount, resp))
+}
+
+fn _tranfer_cw20(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let sender_balance = BALANCES.update(
+        deps.storage,
+        &info.sender,
+        env.block.height,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(amount)?)
+        },
+    )?;
+    // The units backing staked NFTs stay with their owner
+    let staked = _staked_nft_count(deps, env, &info.sender)?;
+    if staked > 0 {
+        let token_info = TOKEN_INFO.load(deps.storage)?;
+        if sender_balance < token_info.units * Uint128::from(staked) {
+            return Err(ContractError::StakedBalance { staked });
+        }
+    }
+    let recipient_balance = BALANCES.update(
+        deps.storage,
+        &recipient,
+        env.block.height,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+    )?;
+
+    // Holders come and go as balances move from zero to non zero and back
+    if !amount.is_zero() {
+        let mut holder_count = HOLDER_COUNT.load(deps.storage)?;
+        if sender_balance.is_zero() {
+            holder_count -= 1;
+        }
+        if recipient_balance == amount {
+            holder_count += 1;
+        }
+        HOLDER_COUNT.save(deps.storage, &holder_count)?;
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "transfer cw20")
+        .add_attribute("from", &info.sender)
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount);
+
+    Ok(res)
+}
+
+// Transfers into the contract's exempt balance, NFTs of the whole units lost by a non exempt
+// sender are banked
+fn _transfer_to_contract(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let balance_before = BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let mut resp = _tranfer_cw20(deps, env, info, env.contract.address.to_string(), amount)?;
+    let balance_after = BALANCES.load(deps.storage, &info.sender)?;
+
+    let is_cw721_exempt = CW721_TRANSFER_EXEMPT
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if !is_cw721_exempt {
+        let nft_to_withdraw_and_store =
+            balance_before / token_info.units - balance_after / token_info.units;
+        for _i in 0..nft_to_withdraw_and_store.u128() {
+            let res = _withdraw_and_store_cw721(deps, env, info, info.sender.to_string())?;
+            resp = resp.add_attributes(res.attributes);
+        }
+    }
+
+    Ok(resp)
+}
+
+// Transfers out of the contract's exempt balance, with NFTs for the whole units gained by a non
+// exempt recipient
+fn _transfer_from_contract(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let balance_before = BALANCES
+        .may_load(deps.storage, &recipient_addr)?
+        .unwrap_or_default();
+    let contract_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    let mut resp = _tranfer_cw20(deps, env, &contract_info, recipient.clone(), amount)?;
+    let balance_after = BALANCES.load(deps.storage, &recipient_addr)?;
+
+    let is_cw721_exempt = CW721_TRANSFER_EXEMPT
+        .may_load(deps.storage, &recipient_addr)?
+        .unwrap_or_default();
+    if !is_cw721_exempt {
+        let nft_to_retrieve_or_mint =
+            balance_after / token_info.units - balance_before / token_info.units;
+        for _i in 0..nft_to_retrieve_or_mint.u128() {
+            let res = _retrieve_or_mint_cw721(deps, info, recipient.clone())?;
+            resp = resp.add_attributes(res.attributes);
+        }
+    }
+
+    Ok(resp)
+}
+
+// Mints new supply to the recipient, with NFTs for the whole units gained by a non exempt
+// recipient. The max NFT supply follows the total supply.
+fn _mint_cw20(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    token_info.total_supply += amount;
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &token_info.total_supply, env.block.height)?;
+    MAX_NFT_SUPPLY.save(deps.storage, &(token_info.total_supply / token_info.units))?;
+
+    let balance_before = BALANCES
+        .may_load(deps.storage, &recipient_addr)?
+        .unwrap_or_default();
+    let balance_after = balance_before + amount;
+    BALANCES.save(
+        deps.storage,
+        &recipient_addr,
+        &balance_after,
+        env.block.height,
+    )?;
+    if balance_before.is_zero() && !amount.is_zero() {
+        HOLDER_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+    }
+
+    let mut resp = Response::new()
+        .add_attribute("action", "mint cw20")
+        .add_attribute("to", &recipient)
+        .add_attribute("amount", amount);
+
+    let is_cw721_exempt = CW721_TRANSFER_EXEMPT
+        .may_load(deps.storage, &recipient_addr)?
+        .unwrap_or_default();
+    if !is_cw721_exempt {
+        let nft_to_retrieve_or_mint =
+            balance_after / token_info.units - balance_before / token_info.units;
+        for _i in 0..nft_to_retrieve_or_mint.u128() {
+            let res = _retrieve_or_mint_cw721(deps, info, recipient.clone())?;
+            resp = resp.add_attributes(res.attributes);
+        }
+    }
+
+    Ok(resp)
+}
+
+// Burns from the owner's balance and the total supply, returns the balance before and after
+fn _burn_balance(
+    deps: &mut DepsMut,
+    env: &Env,
+    owner: &Addr,
+    amount: Uint128,
+) -> Result<(Uint128, Uint128), ContractError> {
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    let balance_before = BALANCES.may_load(deps.storage, owner)?.unwrap_or_default();
+    let balance_after = balance_before.checked_sub(amount)?;
+    let staked = _staked_nft_count(deps, env, owner)?;
+    if balance_after < token_info.units * Uint128::from(staked) {
+        return Err(ContractError::StakedBalance { staked });
+    }
+    BALANCES.save(deps.storage, owner, &balance_after, env.block.height)?;
+    if balance_after.is_zero() && !amount.is_zero() {
+        HOLDER_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count - 1) })?;
+    }
+    token_info.total_supply -= amount;
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+    TOTAL_SUPPLY_HISTORY.save(deps.storage, &token_info.total_supply, env.block.height)?;
+    MAX_NFT_SUPPLY.save(deps.storage, &(token_info.total_supply / token_info.units))?;
+
+    Ok((balance_before, balance_after))
+}
+
+// Burns from the sender's balance, NFTs of the whole units lost by a non exempt sender are banked
+fn _burn_cw20(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let (balance_before, balance_after) = _burn_balance(deps, env, &info.sender, amount)?;
+
+    let mut resp = Response::new()
+        .add_attribute("action", "burn cw20")
+        .add_attribute("from", &info.sender)
+        .add_attribute("amount", amount);
+
+    let is_cw721_exempt = CW721_TRANSFER_EXEMPT
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if !is_cw721_exempt {
+        let nft_to_withdraw_and_store =
+            balance_before / token_info.units - balance_after / token_info.units;
+        for _i in 0..nft_to_withdraw_and_store.u128() {
+            let res = _withdraw_and_store_cw721(deps, env, info, info.sender.to_string())?;
+            resp = resp.add_attributes(res.attributes);
+        }
+    }
+
+    Ok(resp)
+}
+
+fn _retrieve_or_mint_cw721(
+    deps: &mut DepsMut,
+    info: &MessageInfo,
+    to: String,
+) -> Result<Response, ContractError> {
     let is_deque_empty = DEQUE_NFT.is_empty(deps.storage)?;
-    if !is_deque_empty {
+    let token_id = if !is_deque_empty {
         // If there are any tokens in the bank, use those first.
         // Pop off the end of the queue (FIFO).
-        token_id = DEQUE_NFT.pop_back(deps.storage)?.unwrap();
+        DEQUE_NFT.pop_back(deps.storage)?.unwrap()
     } else {
+        // A wrapped collection only hands out the ids deposited into it
+        let token_info = TOKEN_INFO.load(deps.storage)?;
+        if token_info.wrapped_collection.is_some() {
+            return Err(ContractError::EmptyBank {});
+        }
         // Otherwise, mint a new token, should not be able to go over the total fractional supply.
         let nft_count = NFT_COUNT.load(deps.storage)?;
-        token_id = Uint128::from(nft_count).checked_add(Uint128::one())?;
+        if Uint128::from(nft_count) >= MAX_NFT_SUPPLY.load(deps.storage)? {
+            return Err(ContractError::MaxNftSupplyReached {});
+        }
+        let new_token_id = Uint128::from(nft_count).checked_add(Uint128::one())?;
         NFT_COUNT.save(deps.storage, &(nft_count + 1))?;
-    }
-
-    let token_info = TOKEN_INFO.load(deps.storage)?;
-    let mut token_uri = token_info.base_token_uri.unwrap();
-    token_uri.push_str(&token_id.to_string());
+        // Only newly minted ids get traits, banked ids keep the ones they already have
+        generate_traits(deps.storage, new_token_id)?;
+        new_token_id.to_string()
+    };
 
-    _mint_cw721(deps, info, token_id, to, token_uri)
+    _mint_cw721(deps, info, token_id, to)
 }
 
 fn _mint_cw721(
     deps: &mut DepsMut,
     info: &MessageInfo,
-    token_id: Uint128,
+    token_id: String,
     owner: String,
-    token_uri: String,
 ) -> Result<Response, ContractError> {
     let owner = deps.api.addr_validate(&owner)?;
+    // Restore the metadata of a banked token, so its traits stay the same
+    let extension = BANKED_EXTENSIONS
+        .may_load(deps.storage, &token_id)?
+        .flatten();
+    BANKED_EXTENSIONS.remove(deps.storage, &token_id);
     let nft_token = NftInfo {
         owner: owner.clone(),
         approvals: vec![],
-        token_uri: Option::Some(token_uri),
+        // Token uris are built at query time from the metadata config
+        token_uri: None,
+        extension,
     };
-    NFT_TOKENS.update(deps.storage, &token_id.to_string(), |old| match old {
+    NFT_TOKENS.update(deps.storage, &token_id, |old| match old {
         Some(_) => Err(ContractError::Claimed {}),
         None => Ok(nft_token),
     })?;
+    NFT_BALANCES.update(deps.storage, &owner, |count| -> StdResult<_> {
+        Ok(count.unwrap_or_default() + 1)
+    })?;
+    LIVE_NFT_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
 
     let resp = Response::new()
         .add_attribute("action", "mint nft")
@@ -219,30 +2410,85 @@ fn _mint_cw721(
 
 fn _withdraw_and_store_cw721(
     deps: &mut DepsMut,
+    env: &Env,
     info: &MessageInfo,
     sender: String,
 ) -> Result<Response, ContractError> {
     let sender_addr = deps.api.addr_validate(&sender)?;
-    let _limit: Option<u32> = Option::None;
-    let _start: Option<String> = Option::None;
-    let limit: usize = _limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start: Option<Bound<'_, String>> = _start.map(|s| Bound::ExclusiveRaw(s.into()));
-
-    let mut nft_tokens: Vec<String> = NFT_TOKENS
-        .idx
-        .owner
-        .prefix(sender_addr)
-        .keys(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .collect::<StdResult<Vec<_>>>()?;
-    let nft_token_id = nft_tokens.pop().unwrap();
-    let nft_token_id_u128 = nft_token_id.parse::<u128>().unwrap();
+    let start: Option<Bound<'_, String>> = None;
+
+    // Follow the owner's order first, then take the last token of the owner index.
+    // Locked and staked ones are skipped.
+    let nft_order = NFT_ORDER
+        .may_load(deps.storage, &sender_addr)?
+        .unwrap_or_default();
+    let mut nft_token_id = None;
+    for token_id in nft_order {
+        if !_is_nft_protected(deps, env, &sender_addr, &token_id)? {
+            nft_token_id = Some(token_id);
+            break;
+        }
+    }
+    if nft_token_id.is_none() {
+        let token_ids = NFT_TOKENS.idx.owner.prefix(sender_addr.clone()).keys(
+            deps.storage,
+            start,
+            None,
+            Order::Descending,
+        );
+        for token_id in token_ids {
+            let token_id = token_id?;
+            if !_is_nft_protected(deps, env, &sender_addr, &token_id)? {
+                nft_token_id = Some(token_id);
+                break;
+            }
+        }
+    }
+    let nft_token_id = nft_token_id.ok_or(ContractError::OnlyLockedNfts { address: sender })?;
 
     // Record the nft token in the contract's bank queue.
-    DEQUE_NFT.push_front(deps.storage, &Uint128::from(nft_token_id_u128))?;
+    DEQUE_NFT.push_front(deps.storage, &nft_token_id)?;
 
     // burn this nft
-    _burn_nft(deps, &info, nft_token_id)
+    _burn_nft(deps, info, nft_token_id)
+}
+
+// Takes a token out of its owner's stakes, order and NFT balance
+fn _release_nft(deps: &mut DepsMut, owner: &Addr, token_id: &str) -> StdResult<()> {
+    // Only unbonded stakes can be left, their rewards were accrued on unstake
+    NFT_STAKES.remove(deps.storage, (owner, token_id));
+    // Tokens leave the owner's order with the owner
+    if let Some(mut nft_order) = NFT_ORDER.may_load(deps.storage, owner)? {
+        nft_order.retain(|id| id != token_id);
+        if nft_order.is_empty() {
+            NFT_ORDER.remove(deps.storage, owner);
+        } else {
+            NFT_ORDER.save(deps.storage, owner, &nft_order)?;
+        }
+    }
+    let count = NFT_BALANCES
+        .may_load(deps.storage, owner)?
+        .unwrap_or_default();
+    if count > 1 {
+        NFT_BALANCES.save(deps.storage, owner, &(count - 1))?;
+    } else {
+        NFT_BALANCES.remove(deps.storage, owner);
+    }
+    Ok(())
+}
+
+// Hands a live NFT to a new non exempt owner, approvals do not follow it
+fn _move_nft(deps: &mut DepsMut, token_id: &str, recipient: &Addr) -> Result<(), ContractError> {
+    let mut nft_token = NFT_TOKENS.load(deps.storage, token_id)?;
+    _release_nft(deps, &nft_token.owner, token_id)?;
+    NFT_BALANCES.update(deps.storage, recipient, |count| -> StdResult<_> {
+        Ok(count.unwrap_or_default() + 1)
+    })?;
+
+    nft_token.owner = recipient.clone();
+    nft_token.approvals = vec![];
+    NFT_TOKENS.save(deps.storage, token_id, &nft_token)?;
+    Ok(())
 }
 
 fn _burn_nft(
@@ -250,7 +2496,14 @@ fn _burn_nft(
     info: &MessageInfo,
     token_id: String,
 ) -> Result<Response, ContractError> {
+    let nft_token = NFT_TOKENS.load(deps.storage, &token_id)?;
+    // Keep the metadata in the bank, it is restored when the token is minted again
+    if nft_token.extension.is_some() {
+        BANKED_EXTENSIONS.save(deps.storage, &token_id, &nft_token.extension)?;
+    }
     NFT_TOKENS.remove(deps.storage, &token_id)?;
+    LIVE_NFT_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count - 1) })?;
+    _release_nft(deps, &nft_token.owner, &token_id)?;
 
     let resp = Response::new()
         .add_attribute("action", "burn")
@@ -3,6 +3,10 @@ pub mod error;
 pub mod msg;
 pub mod contract;
 pub mod query;
+pub mod render;
 pub mod execute;
+pub mod traits;
+pub mod merkle;
+pub mod sale;
 #[cfg(test)]
 pub mod tests;
\ No newline at end of file
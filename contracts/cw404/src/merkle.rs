@@ -0,0 +1,29 @@
+use cosmwasm_std::{Addr, Binary, Uint128};
+use sha2::{Digest, Sha256};
+
+// Leaf of an (address, allocation) pair, for off-chain tree builders:
+// sha256 of the utf-8 string "<address>:<allocation>", the allocation in decimal fractions,
+// e.g. sha256("orai1...:2500000"). Addresses never contain ':', so every pair has one encoding.
+pub fn merkle_leaf(address: &Addr, allocation: Uint128) -> [u8; 32] {
+    Sha256::digest(format!("{}:{}", address, allocation).as_bytes()).into()
+}
+
+// Pairs are hashed in sorted order, so proofs do not carry the side of each sibling
+pub fn verify_merkle_proof(root: &Binary, leaf: [u8; 32], proof: &[Binary]) -> bool {
+    let mut hash = leaf;
+    for sibling in proof {
+        let Ok(sibling) = <[u8; 32]>::try_from(sibling.as_slice()) else {
+            return false;
+        };
+        let (first, second) = if hash <= sibling {
+            (hash, sibling)
+        } else {
+            (sibling, hash)
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        hash = hasher.finalize().into();
+    }
+    root.as_slice() == hash
+}
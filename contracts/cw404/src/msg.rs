@@ -1,9 +1,23 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{StdError, StdResult, Uint128};
-use cw20::{BalanceResponse, Cw20Coin};
-use cw721::{NumTokensResponse, OwnerOfResponse};
+use cosmwasm_std::{Binary, Coin, StdError, StdResult, Timestamp, Uint128};
+use cw20::{AllAccountsResponse, BalanceResponse, Cw20Coin, Cw20ReceiveMsg, Denom};
+use cw721::{
+    AllNftInfoResponse, Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OwnerOfResponse,
+};
+use cw_utils::Duration;
 
-use cw404_package::{MaxNftSupplyRespone, TokenInfoResponse, Cw721TransferExemptResponse};
+use crate::state::{Extension, RerollFee, TraitLayer};
+use cw404_package::{
+    BankSizeResponse, BankedNftsResponse, CheckInvariantsResponse, CheckRoyaltiesResponse,
+    CurrentPriceResponse, Cw721TransferExemptResponse, Erc721BalanceOfResponse, FeeConfigResponse,
+    FractionStakeResponse, FractionStakingConfigResponse, HolderCountResponse, IsRevealedResponse,
+    LaunchInfoResponse, LockedNftsResponse, MaxNftSupplyRespone, NextFromBankResponse,
+    NftBalanceResponse, NftHoldersResponse, NftOrderResponse, NftStakesResponse,
+    NftStakingConfigResponse, PendingNftRewardsResponse, Pricing, RarityResponse,
+    RenderSvgResponse, RerollFeeResponse, RoyaltiesInfoResponse, SaleInfoResponse,
+    TokenInfoResponse, TotalSupplyAtHeightResponse, VestingResponse, WhitelistClaimedResponse,
+    WhitelistPhasesResponse,
+};
 
 // instantiate msg
 #[cw_serde]
@@ -11,9 +25,22 @@ pub struct InstantiateMsg {
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
+    // Amounts of the instantiate message, initial balances and vesting allocations, are in whole
+    // units
     pub initial_balances: Vec<Cw20Coin>,
     pub admin: String,
     pub base_token_uri: Option<String>,
+    // Trait layers used to generate the traits of each newly minted token
+    pub trait_layers: Option<Vec<TraitLayer>>,
+    // Uri of every token until the admin reveals, unset means revealed from the start
+    pub placeholder_token_uri: Option<String>,
+    // Allocations minted into the contract and released to their beneficiaries over time, one
+    // per beneficiary
+    pub vesting_schedules: Option<Vec<VestingScheduleMsg>>,
+    // Wrapper mode, fractions are only minted by depositing this native denom or cw20 token
+    pub underlying: Option<Denom>,
+    // Wrapper mode, fractions are only minted by sending NFTs of this cw721 collection
+    pub wrapped_collection: Option<String>,
 }
 
 impl InstantiateMsg {
@@ -62,7 +89,214 @@ impl InstantiateMsg {
 pub enum ExecuteMsg {
     // Transfer is a base message to move tokens to another account without triggering actions
     // transfer token also nft
-    Transfer { recipient: String, amount: Uint128 },
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    // Transfer to many (recipient, amount) at once, NFTs move once for the net change of the
+    // whole batch. Fails as a whole with the index of the failing item
+    BatchTransfer {
+        transfers: Vec<(String, Uint128)>,
+    },
+    // Transfer the given NFTs with one unit of fractions each, as (recipient, token_id)
+    BatchTransferNft {
+        transfers: Vec<(String, String)>,
+    },
+    // Only with admin: enable trading from launch_height (current height if unset), non exempt
+    // addresses may receive at most once per block during cooldown_blocks after launch
+    EnableTrading {
+        launch_height: Option<u64>,
+        cooldown_blocks: u64,
+    },
+    // Only with admin: set whether an address is Cw721 transfer exempt (e.g. an AMM pair)
+    SetCw721TransferExempt {
+        address: String,
+        state: bool,
+    },
+    // Only with admin: set buy / sell / transfer fee rates in basis points and the treasury
+    SetFeeConfig {
+        buy_fee_bps: u16,
+        sell_fee_bps: u16,
+        transfer_fee_bps: u16,
+        treasury: String,
+    },
+    // Only with admin: set the collection wide cw2981 royalty, unset removes it
+    SetRoyaltyInfo {
+        royalty: Option<RoyaltyInfoMsg>,
+    },
+    // Only with admin: override the cw2981 royalty of a single token, unset removes the override
+    SetTokenRoyaltyInfo {
+        token_id: String,
+        royalty: Option<RoyaltyInfoMsg>,
+    },
+    // Only with admin: set the on-chain metadata of a token, minted or not
+    SetNftMetadata {
+        token_id: String,
+        extension: Extension,
+    },
+    // Only with admin: replace the trait layers, only tokens minted afterwards are affected
+    SetTraitLayers {
+        layers: Vec<TraitLayer>,
+    },
+    // Only with admin: commit the sha256 hash of the seed opened at reveal
+    CommitReveal {
+        seed_hash: Binary,
+    },
+    // Only with admin: reveal the collection, the seed is required if a seed hash was committed
+    // and shuffles the ids of revealed token uris. Traits are not secret before reveal, queries
+    // only start showing them.
+    Reveal {
+        seed: Option<Binary>,
+    },
+    // Only with admin: update the base token uri and optionally the token uri template,
+    // e.g. "{base}{id}.json"
+    UpdateBaseTokenUri {
+        base_token_uri: Option<String>,
+        token_uri_template: Option<String>,
+    },
+    // Only with admin: override the token uri of a single token, unset removes the override
+    SetTokenUri {
+        token_id: String,
+        token_uri: Option<String>,
+    },
+    // Only with admin: freeze token uris and metadata, no further changes are possible
+    FreezeMetadata {},
+    // Mint or bank NFTs until the NFTs of a non exempt address match its whole units
+    Reconcile {
+        address: String,
+    },
+    // Protect an owned NFT from being banked by fractional transfers
+    LockNft {
+        token_id: String,
+    },
+    // Remove the protection of an owned NFT
+    UnlockNft {
+        token_id: String,
+    },
+    // Set the order in which the caller's NFTs leave on fractional transfers, unlisted ones leave
+    // after the listed ones, an empty list resets the order
+    SetNftOrder {
+        token_ids: Vec<String>,
+    },
+    // Only with admin: set the reward denom and rate of staked NFTs and their unbonding period
+    SetNftStakingConfig {
+        reward_denom: Denom,
+        reward_per_block: Uint128,
+        unbonding_period: Duration,
+    },
+    // Stake an owned NFT, it can not be banked and its units of balance can not be moved
+    StakeNft {
+        token_id: String,
+    },
+    // Stop earning on a staked NFT, it stays in custody until the unbonding period ends
+    UnstakeNft {
+        token_id: String,
+    },
+    // Claim the rewards of the caller's staked NFTs
+    ClaimRewards {},
+    // Only with admin: set the reward denom and rate shared by staked fractions
+    SetFractionStakingConfig {
+        reward_denom: Denom,
+        reward_per_block: Uint128,
+    },
+    // Stake fractions of the caller's balance, NFTs of the whole units leaving are banked
+    StakeFractions {
+        amount: Uint128,
+    },
+    // Unstake fractions back to the caller's balance, NFTs are issued for whole units gained
+    UnstakeFractions {
+        amount: Uint128,
+    },
+    // Claim the rewards of the caller's staked fractions
+    ClaimFractionRewards {},
+    // Only with admin: set the native coin sale of newly minted supply, caps are in fractions
+    SetSaleConfig {
+        denom: String,
+        pricing: Pricing,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        per_wallet_cap: Uint128,
+        total_cap: Uint128,
+    },
+    // Buy the given amount of newly minted fractions with the sale denom, within the caps, or as
+    // much as the payment allows when unset. The part of the payment above the cost is refunded.
+    BuyWithNative {
+        amount: Option<Uint128>,
+    },
+    // Only with admin: set a whitelist phase, its merkle root is over (address, allocation) pairs.
+    // Phases without a price are airdrops.
+    SetWhitelistPhase {
+        phase_id: u64,
+        merkle_root: Binary,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        price_per_unit: Option<Coin>,
+    },
+    // Claim the whole airdrop allocation proven by the proof in an active phase
+    ClaimAirdrop {
+        amount: Uint128,
+        proof: Vec<Binary>,
+    },
+    // Buy fractions within the allocation proven by the proof in an active phase, the part of
+    // the payment above the allocation is refunded
+    WhitelistBuy {
+        amount: Uint128,
+        proof: Vec<Binary>,
+    },
+    // Only with admin: send the sale and whitelist proceeds to the recipient, the admin by default
+    WithdrawProceeds {
+        recipient: Option<String>,
+    },
+    // Only with admin: lock an amount of the admin's balance in a vesting schedule. A beneficiary
+    // has one schedule at a time, a new one is rejected until the current one is fully claimed
+    CreateVestingSchedule {
+        schedule: VestingScheduleMsg,
+    },
+    // Transfer the vested and not yet claimed amount of the caller's schedule
+    ClaimVested {},
+    // Only with admin: set the fee charged on reroll, unset removes it
+    SetRerollFee {
+        fee: Option<RerollFee>,
+    },
+    // Bank the given NFT of the caller and get a different one from the bank, or a newly minted one
+    Reroll {
+        token_id: String,
+    },
+    // Wrapper mode: lock the sent underlying native coins and mint the same amount of fractions
+    Deposit {},
+    // Wrapper mode: cw20 hook, lock the sent underlying tokens and mint the same amount of fractions
+    Receive(Cw20ReceiveMsg),
+    // Wrapper mode: burn fractions and get the same amount of the underlying asset back
+    Withdraw {
+        amount: Uint128,
+    },
+    // Wrapper mode: cw721 hook, wrap the sent NFT into units of fractions and a mirror with its id
+    ReceiveNft(Cw721ReceiveMsg),
+    // Wrapper mode: burn the caller's mirror and the units backing it, and get the original back
+    RedeemNft {
+        token_id: String,
+    },
+}
+
+// migrate msg
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+pub struct RoyaltyInfoMsg {
+    pub payment_address: String,
+    // Percentage of the sale price, 0-100
+    pub royalty_percentage: u64,
+}
+
+#[cw_serde]
+pub struct VestingScheduleMsg {
+    pub beneficiary: String,
+    // Whole units in the instantiate message, fractions in CreateVestingSchedule like every
+    // other execute amount
+    pub amount: Uint128,
+    pub cliff: Duration,
+    pub duration: Duration,
 }
 
 // query msg
@@ -76,6 +310,21 @@ pub enum QueryMsg {
     // Returns metadata on the contract - name, decimals, supply, etc.
     #[returns(TokenInfoResponse)]
     TokenInfo {},
+    // Returns all accounts that have balances. Supports pagination.
+    #[returns(AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Returns the number of accounts with a non zero balance
+    #[returns(HolderCountResponse)]
+    HolderCount {},
+    // Returns the balance of the given address at the beginning of the given height
+    #[returns(BalanceResponse)]
+    BalanceAtHeight { address: String, height: u64 },
+    // Returns the total supply at the beginning of the given height
+    #[returns(TotalSupplyAtHeightResponse)]
+    TotalSupplyAtHeight { height: u64 },
 
     /// Cw721 query
     // Return the owner of the given token, error if token does not exist
@@ -85,6 +334,47 @@ pub enum QueryMsg {
         /// unset or false will filter out expired approvals, you must set to true to see them
         include_expired: Option<bool>,
     },
+    // With MetaData Extension.
+    // Returns metadata about one particular token, based on *ERC721 Metadata JSON Schema*
+    #[returns(NftInfoResponse<Extension>)]
+    NftInfo { token_id: String },
+    // With MetaData Extension.
+    // Returns the result of both `NftInfo` and `OwnerOf` as one query as an optimization
+    #[returns(AllNftInfoResponse<Extension>)]
+    AllNftInfo {
+        token_id: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+    },
+    // Whole units held by the given address at the beginning of the given height, for NFT
+    // weighted voting
+    #[returns(NftBalanceResponse)]
+    NftBalanceAtHeight { address: String, height: u64 },
+    // Number of NFTs held by the given owner, like ERC721 balanceOf
+    #[returns(NftBalanceResponse)]
+    NftBalance { owner: String },
+    // Returns the distinct owners of NFTs. Supports pagination.
+    #[returns(NftHoldersResponse)]
+    NftHolders {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Returns the NFTs the given owner protected from fractional transfers
+    #[returns(LockedNftsResponse)]
+    LockedNfts { owner: String },
+    // Returns the order in which the given owner's listed NFTs leave on fractional transfers
+    #[returns(NftOrderResponse)]
+    NftOrder { owner: String },
+    // Compare the NFTs held by the given owner with the whole units of its balance
+    #[returns(Erc721BalanceOfResponse)]
+    Erc721BalanceOf { owner: String },
+    // Admin tooling: page through accounts and report the non exempt ones whose NFTs do not
+    // match their whole units, and check live plus banked NFTs against the minted count
+    #[returns(CheckInvariantsResponse)]
+    CheckInvariants {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     // Total number of tokens issued
     #[returns(NumTokensResponse)]
     NftNumTokens {},
@@ -94,4 +384,104 @@ pub enum QueryMsg {
     // Cw721 transfer exempt
     #[returns(Cw721TransferExemptResponse)]
     Cw721TransferExempt { address: String },
+
+    /// Launch query
+    // Returns the trading gate and anti-bot cooldown config
+    #[returns(LaunchInfoResponse)]
+    LaunchInfo {},
+
+    /// Fee query
+    // Returns the buy / sell / transfer fee rates and treasury
+    #[returns(FeeConfigResponse)]
+    FeeConfig {},
+
+    /// Cw2981 query
+    // Returns the royalty address and amount to pay for a sale of the given token
+    #[returns(RoyaltiesInfoResponse)]
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    // Returns whether this contract implements royalties
+    #[returns(CheckRoyaltiesResponse)]
+    CheckRoyalties {},
+
+    /// Trait query
+    // Returns the rarity score and tier of a token with generated traits
+    #[returns(RarityResponse)]
+    RarityOf { token_id: String },
+
+    /// Reveal query
+    // Returns whether the collection metadata is revealed
+    #[returns(IsRevealedResponse)]
+    IsRevealed {},
+
+    /// Render query
+    // Returns the token rendered on-chain as an svg data uri
+    #[returns(RenderSvgResponse)]
+    RenderSvg { token_id: String },
+
+    /// Nft staking query
+    // Returns the reward denom and rate of staked NFTs and their unbonding period
+    #[returns(NftStakingConfigResponse)]
+    NftStakingConfig {},
+    // Returns the NFTs staked by the given owner, unbonding ones included. Supports pagination.
+    #[returns(NftStakesResponse)]
+    NftStakes {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Returns the rewards the given owner can claim
+    #[returns(PendingNftRewardsResponse)]
+    PendingNftRewards { owner: String },
+
+    /// Fraction staking query
+    // Returns the reward denom and rate shared by staked fractions and the total staked
+    #[returns(FractionStakingConfigResponse)]
+    FractionStakingConfig {},
+    // Returns the fractions staked by the given address and its pending rewards
+    #[returns(FractionStakeResponse)]
+    FractionStake { address: String },
+
+    /// Sale query
+    // Returns the current sale config and progress
+    #[returns(SaleInfoResponse)]
+    SaleInfo {},
+    // Returns the cost of buying the given amount of fractions in the sale now
+    #[returns(CurrentPriceResponse)]
+    CurrentPrice { amount: Uint128 },
+    // Returns the whitelist phases. Supports pagination.
+    #[returns(WhitelistPhasesResponse)]
+    WhitelistPhases {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Returns the fractions claimed or bought by the given address in the given phase
+    #[returns(WhitelistClaimedResponse)]
+    WhitelistClaimed { phase_id: u64, address: String },
+
+    /// Vesting query
+    // Returns the vested, claimed and locked amounts of the given beneficiary's schedule
+    #[returns(VestingResponse)]
+    Vesting { beneficiary: String },
+
+    /// Reroll query
+    // Returns the fee charged on reroll
+    #[returns(RerollFeeResponse)]
+    RerollFee {},
+
+    /// Bank query
+    // Returns the banked NFT ids in pop order, start is the position in line. Supports pagination.
+    #[returns(BankedNftsResponse)]
+    BankedNfts {
+        start: Option<u32>,
+        limit: Option<u32>,
+    },
+    // Returns the number of banked NFT ids
+    #[returns(BankSizeResponse)]
+    BankSize {},
+    // Returns the id the next retrieved or minted NFT will get
+    #[returns(NextFromBankResponse)]
+    NextFromBank {},
 }
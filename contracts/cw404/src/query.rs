@@ -1,14 +1,36 @@
-use cosmwasm_std::{BlockInfo, Deps, Env, StdResult};
-use cw20::BalanceResponse;
-use cw721::{NumTokensResponse, OwnerOfResponse};
+use cosmwasm_std::{BlockInfo, Deps, Env, Order, StdError, StdResult, Uint128};
+use cw20::{AllAccountsResponse, BalanceResponse};
+use cw721::{AllNftInfoResponse, NftInfoResponse, NumTokensResponse, OwnerOfResponse};
+use cw_storage_plus::Bound;
 
-use cw404_package::{Cw721TransferExemptResponse, MaxNftSupplyRespone, TokenInfoResponse};
+use cw404_package::{
+    AccountMismatch, BankSizeResponse, BankedNftsResponse, CheckInvariantsResponse,
+    CheckRoyaltiesResponse, CurrentPriceResponse, Cw721TransferExemptResponse,
+    Erc721BalanceOfResponse, FeeConfigResponse, FractionStakeResponse,
+    FractionStakingConfigResponse, HolderCountResponse, IsRevealedResponse, LaunchInfoResponse,
+    LockedNftsResponse, MaxNftSupplyRespone, NextFromBankResponse, NftBalanceResponse,
+    NftHoldersResponse, NftOrderResponse, NftStakeInfo, NftStakesResponse,
+    NftStakingConfigResponse, PendingNftRewardsResponse, RarityResponse, RenderSvgResponse,
+    RerollFeeResponse, RoyaltiesInfoResponse, SaleInfoResponse, TokenInfoResponse,
+    TotalSupplyAtHeightResponse, VestingResponse, WhitelistClaimedResponse, WhitelistPhaseInfo,
+    WhitelistPhasesResponse,
+};
 
+use crate::render::{render_svg, svg_data_uri};
+use crate::sale::sale_cost;
 use crate::state::{
-    Approval, NftInfo, BALANCES, CW721_TRANSFER_EXEMPT, MAX_NFT_SUPPLY, NFT_COUNT, NFT_TOKENS,
-    TOKEN_INFO,
+    Approval, Extension, NftInfo, RerollFee, BALANCES, CW721_TRANSFER_EXEMPT, DEQUE_NFT,
+    FEE_CONFIG, FRACTION_REWARD_INDEX, FRACTION_STAKES, FRACTION_STAKING_CONFIG, HOLDER_COUNT,
+    LAUNCH_INFO, LIVE_NFT_COUNT, LOCKED_NFTS, MAX_NFT_SUPPLY, METADATA_CONFIG, NFT_BALANCES,
+    NFT_COUNT, NFT_ORDER, NFT_REWARD_INDEX, NFT_STAKES, NFT_STAKING_CONFIG, NFT_STAKING_REWARDS,
+    NFT_TOKENS, REROLL_FEE, REVEAL_INFO, ROYALTY_INFO, SALE_CONFIG, TOKEN_INFO, TOKEN_RARITY,
+    TOKEN_ROYALTY_INFO, TOKEN_URI_OVERRIDES, TOTAL_SUPPLY_HISTORY, VESTING_SCHEDULES,
+    WHITELIST_CLAIMED, WHITELIST_PHASES,
 };
 
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
 pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
     let balance = BALANCES
@@ -18,6 +40,69 @@ pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse>
     Ok(BalanceResponse { balance })
 }
 
+pub fn query_all_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllAccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let accounts = BALANCES
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|address| address.map(String::from))
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllAccountsResponse { accounts })
+}
+
+pub fn query_holder_count(deps: Deps) -> StdResult<HolderCountResponse> {
+    let count = HOLDER_COUNT.load(deps.storage)?;
+
+    Ok(HolderCountResponse { count })
+}
+
+pub fn query_balance_at_height(
+    deps: Deps,
+    address: String,
+    height: u64,
+) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_total_supply_at_height(
+    deps: Deps,
+    height: u64,
+) -> StdResult<TotalSupplyAtHeightResponse> {
+    let total_supply = TOTAL_SUPPLY_HISTORY
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+
+    Ok(TotalSupplyAtHeightResponse { total_supply })
+}
+
+pub fn query_nft_balance_at_height(
+    deps: Deps,
+    address: String,
+    height: u64,
+) -> StdResult<NftBalanceResponse> {
+    let balance = query_balance_at_height(deps, address, height)?.balance;
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let count = u64::try_from((balance / token_info.units).u128())
+        .map_err(|_| StdError::generic_err("Nft balance overflow"))?;
+
+    Ok(NftBalanceResponse { count })
+}
+
 pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
     let info = TOKEN_INFO.load(deps.storage)?;
     let resp = TokenInfoResponse {
@@ -27,17 +112,152 @@ pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
         total_supply: info.total_supply,
         admin: info.admin,
         units: info.units,
+        underlying: info.underlying,
+        wrapped_collection: info.wrapped_collection,
     };
 
     Ok(resp)
 }
 
+pub fn query_nft_balance(deps: Deps, owner: String) -> StdResult<NftBalanceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let count = NFT_BALANCES
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default();
+
+    Ok(NftBalanceResponse { count })
+}
+
+pub fn query_nft_holders(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<NftHoldersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let owners = NFT_BALANCES
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|address| address.map(String::from))
+        .collect::<StdResult<_>>()?;
+
+    Ok(NftHoldersResponse { owners })
+}
+
+pub fn query_locked_nfts(deps: Deps, owner: String) -> StdResult<LockedNftsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let token_ids = LOCKED_NFTS
+        .prefix(&owner)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    Ok(LockedNftsResponse { token_ids })
+}
+
+pub fn query_nft_order(deps: Deps, owner: String) -> StdResult<NftOrderResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let token_ids = NFT_ORDER
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default();
+
+    Ok(NftOrderResponse { token_ids })
+}
+
+pub fn query_erc721_balance_of(deps: Deps, owner: String) -> StdResult<Erc721BalanceOfResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let count = NFT_BALANCES
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default();
+    let balance = BALANCES.may_load(deps.storage, &owner)?.unwrap_or_default();
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let expected = u64::try_from((balance / token_info.units).u128())
+        .map_err(|_| StdError::generic_err("Nft balance overflow"))?;
+    let exempt = CW721_TRANSFER_EXEMPT
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default();
+
+    Ok(Erc721BalanceOfResponse {
+        count,
+        expected,
+        exempt,
+        in_sync: exempt || count == expected,
+    })
+}
+
+pub fn query_check_invariants(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<CheckInvariantsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+
+    let accounts = BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut mismatches: Vec<AccountMismatch> = vec![];
+    for (address, balance) in accounts.iter() {
+        let exempt = CW721_TRANSFER_EXEMPT
+            .may_load(deps.storage, address)?
+            .unwrap_or_default();
+        if exempt {
+            continue;
+        }
+        let expected = u64::try_from((*balance / token_info.units).u128())
+            .map_err(|_| StdError::generic_err("Nft balance overflow"))?;
+        let count = NFT_BALANCES
+            .may_load(deps.storage, address)?
+            .unwrap_or_default();
+        if count != expected {
+            mismatches.push(AccountMismatch {
+                address: address.to_string(),
+                expected,
+                count,
+            });
+        }
+    }
+
+    let live_nfts = LIVE_NFT_COUNT.load(deps.storage)?;
+    let banked_nfts = u64::from(DEQUE_NFT.len(deps.storage)?);
+    let nft_count = existing_nft_count(deps)?;
+    let max_nft_supply = MAX_NFT_SUPPLY.load(deps.storage)?;
+
+    Ok(CheckInvariantsResponse {
+        mismatches,
+        last_account: accounts.last().map(|(address, _)| address.to_string()),
+        live_nfts,
+        banked_nfts,
+        nft_count,
+        totals_in_sync: live_nfts + banked_nfts == nft_count
+            && Uint128::from(live_nfts) <= max_nft_supply,
+    })
+}
+
 pub fn query_nft_num_token(deps: Deps) -> StdResult<NumTokensResponse> {
-    let nft_count = NFT_COUNT.load(deps.storage)?;
+    let nft_count = existing_nft_count(deps)?;
 
     Ok(NumTokensResponse { count: nft_count })
 }
 
+// NFTs minted so far. A wrapped collection mints none, its deposited NFTs each back one unit.
+fn existing_nft_count(deps: Deps) -> StdResult<u64> {
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    if token_info.wrapped_collection.is_none() {
+        return NFT_COUNT.load(deps.storage);
+    }
+    u64::try_from((token_info.total_supply / token_info.units).u128())
+        .map_err(|_| StdError::generic_err("Nft count overflow"))
+}
+
 pub fn query_max_nft_supply(deps: Deps) -> StdResult<MaxNftSupplyRespone> {
     let max_nft_supply = MAX_NFT_SUPPLY.load(deps.storage)?;
 
@@ -56,6 +276,69 @@ pub fn query_cw721_transfer_exempt(
     Ok(Cw721TransferExemptResponse { state })
 }
 
+pub fn query_launch_info(deps: Deps) -> StdResult<LaunchInfoResponse> {
+    let launch_info = LAUNCH_INFO.load(deps.storage)?;
+
+    Ok(LaunchInfoResponse {
+        trading_enabled: launch_info.trading_enabled,
+        launch_height: launch_info.launch_height,
+        cooldown_blocks: launch_info.cooldown_blocks,
+    })
+}
+
+pub fn query_fee_config(deps: Deps) -> StdResult<FeeConfigResponse> {
+    let fee_config = FEE_CONFIG.load(deps.storage)?;
+
+    Ok(FeeConfigResponse {
+        buy_fee_bps: fee_config.buy_fee_bps,
+        sell_fee_bps: fee_config.sell_fee_bps,
+        transfer_fee_bps: fee_config.transfer_fee_bps,
+        treasury: fee_config.treasury,
+    })
+}
+
+pub fn query_royalty_info(
+    deps: Deps,
+    token_id: String,
+    sale_price: Uint128,
+) -> StdResult<RoyaltiesInfoResponse> {
+    // A per token override takes precedence over the collection wide royalty
+    let royalty_info = match TOKEN_ROYALTY_INFO.may_load(deps.storage, &token_id)? {
+        Some(royalty_info) => Some(royalty_info),
+        None => ROYALTY_INFO.may_load(deps.storage)?,
+    };
+
+    Ok(match royalty_info {
+        Some(royalty_info) => RoyaltiesInfoResponse {
+            address: royalty_info.payment_address.to_string(),
+            royalty_amount: sale_price.multiply_ratio(royalty_info.royalty_percentage, 100u128),
+        },
+        None => RoyaltiesInfoResponse {
+            address: String::new(),
+            royalty_amount: Uint128::zero(),
+        },
+    })
+}
+
+pub fn query_check_royalties(_deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
+    Ok(CheckRoyaltiesResponse {
+        royalty_payments: true,
+    })
+}
+
+pub fn query_rarity_of(deps: Deps, token_id: String) -> StdResult<RarityResponse> {
+    if !REVEAL_INFO.load(deps.storage)?.revealed {
+        return Err(StdError::generic_err("Traits are not shown until reveal"));
+    }
+    let rarity = TOKEN_RARITY.load(deps.storage, &token_id)?;
+
+    Ok(RarityResponse {
+        token_id,
+        score: rarity.score,
+        tier: rarity.tier,
+    })
+}
+
 pub fn query_owner_of(
     deps: Deps,
     env: Env,
@@ -70,9 +353,114 @@ pub fn query_owner_of(
     })
 }
 
-fn humanize_approvals(
+pub fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse<Extension>> {
+    let nft_info = NFT_TOKENS.load(deps.storage, &token_id)?;
+
+    Ok(NftInfoResponse {
+        token_uri: resolve_token_uri(deps, &token_id)?,
+        extension: revealed_extension(deps, nft_info.extension)?,
+    })
+}
+
+pub fn query_all_nft_info(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<AllNftInfoResponse<Extension>> {
+    let nft_info = NFT_TOKENS.load(deps.storage, &token_id)?;
+
+    Ok(AllNftInfoResponse {
+        access: OwnerOfResponse {
+            owner: nft_info.owner.to_string(),
+            approvals: humanize_approvals(&env.block, &nft_info, include_expired),
+        },
+        info: NftInfoResponse {
+            token_uri: resolve_token_uri(deps, &token_id)?,
+            extension: revealed_extension(deps, nft_info.extension)?,
+        },
+    })
+}
+
+pub fn query_render_svg(deps: Deps, token_id: String) -> StdResult<RenderSvgResponse> {
+    let nft_info = NFT_TOKENS.load(deps.storage, &token_id)?;
+    // Ids of a wrapped collection may not be numeric, their bytes pick the colour instead
+    let id = token_id
+        .parse::<u128>()
+        .unwrap_or_else(|_| token_id.bytes().map(u128::from).sum());
+
+    // Before reveal only the placeholder is rendered, without name or traits
+    let metadata = revealed_extension(deps, nft_info.extension)?.unwrap_or_default();
+    let title = match metadata.name {
+        Some(name) => name,
+        None => format!("{} #{}", TOKEN_INFO.load(deps.storage)?.symbol, token_id),
+    };
+    let svg = render_svg(id, &title, &metadata.attributes.unwrap_or_default())?;
+
+    Ok(RenderSvgResponse {
+        token_id,
+        data_uri: svg_data_uri(&svg),
+    })
+}
+
+pub fn query_is_revealed(deps: Deps) -> StdResult<IsRevealedResponse> {
+    let reveal_info = REVEAL_INFO.load(deps.storage)?;
+
+    Ok(IsRevealedResponse {
+        revealed: reveal_info.revealed,
+    })
+}
+
+// Metadata reported by queries, left out until reveal. This only changes what queries show:
+// traits are derived at mint from the public collection seed and stored as is, so raw storage
+// reads see them before reveal.
+fn revealed_extension(deps: Deps, extension: Extension) -> StdResult<Extension> {
+    let reveal_info = REVEAL_INFO.load(deps.storage)?;
+    if !reveal_info.revealed {
+        return Ok(None);
+    }
+    Ok(extension)
+}
+
+// Token uri reported by queries: the placeholder before reveal, then the token's override if
+// any, otherwise the uri template filled with the base token uri and the (shuffled) id
+fn resolve_token_uri(deps: Deps, token_id: &str) -> StdResult<Option<String>> {
+    let reveal_info = REVEAL_INFO.load(deps.storage)?;
+    if !reveal_info.revealed {
+        return Ok(reveal_info.placeholder_uri);
+    }
+    if let Some(token_uri) = TOKEN_URI_OVERRIDES.may_load(deps.storage, token_id)? {
+        return Ok(Some(token_uri));
+    }
+
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let base_token_uri = match token_info.base_token_uri {
+        Some(base_token_uri) => base_token_uri,
+        None => return Ok(None),
+    };
+    // Ids minted after reveal, and ids outside the sequence, keep their own uri
+    let uri_id = match (reveal_info.offset, reveal_info.revealed_supply) {
+        (Some(offset), Some(revealed_supply)) => match token_id.parse::<u128>() {
+            Ok(id) if id >= 1 && id <= revealed_supply.u128() => {
+                ((id - 1 + u128::from(offset)) % revealed_supply.u128() + 1).to_string()
+            }
+            _ => token_id.to_string(),
+        },
+        _ => token_id.to_string(),
+    };
+    let metadata_config = METADATA_CONFIG.load(deps.storage)?;
+
+    Ok(Some(
+        metadata_config
+            .token_uri_template
+            .replace("{base}", &base_token_uri)
+            .replace("{id}", &uri_id),
+    ))
+}
+
+fn humanize_approvals<T>(
     block: &BlockInfo,
-    nft_info: &NftInfo,
+    nft_info: &NftInfo<T>,
     include_expired: bool,
 ) -> Vec<cw721::Approval> {
     nft_info
@@ -89,3 +477,271 @@ fn humanize_approval(approval: &Approval) -> cw721::Approval {
         expires: approval.expires,
     }
 }
+
+pub fn query_nft_staking_config(deps: Deps) -> StdResult<NftStakingConfigResponse> {
+    let config = NFT_STAKING_CONFIG.load(deps.storage)?;
+
+    Ok(NftStakingConfigResponse {
+        reward_denom: config.reward_denom,
+        reward_per_block: config.reward_per_block,
+        unbonding_period: config.unbonding_period,
+    })
+}
+
+pub fn query_nft_stakes(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<NftStakesResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let reward_index = nft_reward_index(deps, &env)?;
+
+    let stakes = NFT_STAKES
+        .prefix(&owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (token_id, stake) = item?;
+            Ok(NftStakeInfo {
+                token_id,
+                unbonding: stake.unbonding,
+                pending_rewards: stake.pending_rewards(reward_index),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(NftStakesResponse { stakes })
+}
+
+pub fn query_pending_nft_rewards(
+    deps: Deps,
+    env: Env,
+    owner: String,
+) -> StdResult<PendingNftRewardsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let reward_index = nft_reward_index(deps, &env)?;
+
+    let mut rewards = NFT_STAKING_REWARDS
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default();
+    for item in NFT_STAKES
+        .prefix(&owner)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, stake) = item?;
+        rewards += stake.pending_rewards(reward_index);
+    }
+
+    Ok(PendingNftRewardsResponse { rewards })
+}
+
+fn nft_reward_index(deps: Deps, env: &Env) -> StdResult<Uint128> {
+    let config = NFT_STAKING_CONFIG.load(deps.storage)?;
+    let reward_index = NFT_REWARD_INDEX.load(deps.storage)?;
+    Ok(reward_index.current(config.reward_per_block, &env.block))
+}
+
+pub fn query_fraction_staking_config(deps: Deps) -> StdResult<FractionStakingConfigResponse> {
+    let config = FRACTION_STAKING_CONFIG.load(deps.storage)?;
+    let reward_index = FRACTION_REWARD_INDEX.load(deps.storage)?;
+
+    Ok(FractionStakingConfigResponse {
+        reward_denom: config.reward_denom,
+        reward_per_block: config.reward_per_block,
+        total_staked: reward_index.total_staked,
+    })
+}
+
+pub fn query_fraction_stake(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<FractionStakeResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let Some(mut stake) = FRACTION_STAKES.may_load(deps.storage, &address)? else {
+        return Ok(FractionStakeResponse {
+            amount: Uint128::zero(),
+            pending_rewards: Uint128::zero(),
+        });
+    };
+    let config = FRACTION_STAKING_CONFIG.load(deps.storage)?;
+    let reward_index = FRACTION_REWARD_INDEX
+        .load(deps.storage)?
+        .current(config.reward_per_block, &env.block);
+    stake.accrue(reward_index);
+
+    Ok(FractionStakeResponse {
+        amount: stake.amount,
+        pending_rewards: stake.pending_rewards,
+    })
+}
+
+pub fn query_sale_info(deps: Deps, env: Env) -> StdResult<SaleInfoResponse> {
+    let sale_config = SALE_CONFIG.load(deps.storage)?;
+
+    Ok(SaleInfoResponse {
+        active: sale_config.is_active(&env.block),
+        denom: sale_config.denom,
+        pricing: sale_config.pricing,
+        start_time: sale_config.start_time,
+        end_time: sale_config.end_time,
+        per_wallet_cap: sale_config.per_wallet_cap,
+        total_cap: sale_config.total_cap,
+        sold: sale_config.sold,
+        proceeds: sale_config.proceeds,
+    })
+}
+
+pub fn query_current_price(
+    deps: Deps,
+    env: Env,
+    amount: Uint128,
+) -> StdResult<CurrentPriceResponse> {
+    let sale_config = SALE_CONFIG.load(deps.storage)?;
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let price = sale_cost(
+        &sale_config.pricing,
+        env.block.height,
+        sale_config.sold,
+        token_info.units,
+        amount,
+    )?;
+
+    Ok(CurrentPriceResponse {
+        denom: sale_config.denom,
+        price,
+    })
+}
+
+pub fn query_whitelist_phases(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<WhitelistPhasesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let phases = WHITELIST_PHASES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (phase_id, phase) = item?;
+            Ok(WhitelistPhaseInfo {
+                phase_id,
+                active: phase.is_active(&env.block),
+                merkle_root: phase.merkle_root,
+                start_time: phase.start_time,
+                end_time: phase.end_time,
+                price_per_unit: phase.price_per_unit,
+                proceeds: phase.proceeds,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(WhitelistPhasesResponse { phases })
+}
+
+pub fn query_whitelist_claimed(
+    deps: Deps,
+    phase_id: u64,
+    address: String,
+) -> StdResult<WhitelistClaimedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let claimed = WHITELIST_CLAIMED
+        .may_load(deps.storage, (phase_id, &address))?
+        .unwrap_or_default();
+
+    Ok(WhitelistClaimedResponse { claimed })
+}
+
+pub fn query_vesting(deps: Deps, env: Env, beneficiary: String) -> StdResult<VestingResponse> {
+    let beneficiary = deps.api.addr_validate(&beneficiary)?;
+    // Fully claimed schedules are removed, they report nothing left
+    let Some(schedule) = VESTING_SCHEDULES.may_load(deps.storage, &beneficiary)? else {
+        return Ok(VestingResponse {
+            total: Uint128::zero(),
+            vested: Uint128::zero(),
+            claimed: Uint128::zero(),
+            locked: Uint128::zero(),
+        });
+    };
+    let vested = schedule.vested(&env.block);
+
+    Ok(VestingResponse {
+        total: schedule.total,
+        vested,
+        claimed: schedule.claimed,
+        locked: schedule.total - vested,
+    })
+}
+
+pub fn query_reroll_fee(deps: Deps) -> StdResult<RerollFeeResponse> {
+    let resp = match REROLL_FEE.may_load(deps.storage)? {
+        Some(RerollFee::Native(coin)) => RerollFeeResponse {
+            native: Some(coin),
+            fractions: None,
+        },
+        Some(RerollFee::Fractions(amount)) => RerollFeeResponse {
+            native: None,
+            fractions: Some(amount),
+        },
+        None => RerollFeeResponse {
+            native: None,
+            fractions: None,
+        },
+    };
+
+    Ok(resp)
+}
+
+pub fn query_banked_nfts(
+    deps: Deps,
+    start: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<BankedNftsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let start = start.unwrap_or_default();
+    let len = DEQUE_NFT.len(deps.storage)?;
+
+    // Ids are popped off the back of the deque, so walk it from the back
+    let token_ids = (start..len.min(start.saturating_add(limit)))
+        .map(|position| {
+            DEQUE_NFT
+                .get(deps.storage, len - 1 - position)?
+                .ok_or_else(|| StdError::not_found("banked nft"))
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(BankedNftsResponse { token_ids })
+}
+
+pub fn query_bank_size(deps: Deps) -> StdResult<BankSizeResponse> {
+    let size = u64::from(DEQUE_NFT.len(deps.storage)?);
+
+    Ok(BankSizeResponse { size })
+}
+
+pub fn query_next_from_bank(deps: Deps) -> StdResult<NextFromBankResponse> {
+    match DEQUE_NFT.back(deps.storage)? {
+        Some(token_id) => Ok(NextFromBankResponse {
+            token_id,
+            from_bank: true,
+        }),
+        None => {
+            // A wrapped collection only hands out deposited ids
+            if TOKEN_INFO.load(deps.storage)?.wrapped_collection.is_some() {
+                return Err(StdError::generic_err("No NFT in the bank to hand out"));
+            }
+            let nft_count = NFT_COUNT.load(deps.storage)?;
+            Ok(NextFromBankResponse {
+                token_id: (nft_count + 1).to_string(),
+                from_bank: false,
+            })
+        }
+    }
+}
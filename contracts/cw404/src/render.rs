@@ -0,0 +1,67 @@
+use cosmwasm_std::{Binary, StdError, StdResult};
+
+use crate::state::Trait;
+
+// Size limits of rendered svgs
+pub const MAX_SVG_BYTES: usize = 8 * 1024;
+const MAX_RENDERED_TRAITS: usize = 12;
+const MAX_TEXT_CHARS: usize = 32;
+
+const SVG_SIZE: u32 = 350;
+const LINE_HEIGHT: u32 = 24;
+
+// Render a token as an svg document: the title on a background derived from the token id,
+// followed by one line per trait.
+pub fn render_svg(token_id: u128, title: &str, traits: &[Trait]) -> StdResult<String> {
+    let hue = (token_id % 360) * 137 % 360;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" width=\"{size}\" height=\"{size}\">\
+         <rect width=\"100%\" height=\"100%\" fill=\"hsl({hue},60%,20%)\"/>\
+         <text x=\"20\" y=\"40\" font-family=\"monospace\" font-size=\"20\" fill=\"#ffffff\">{title}</text>",
+        size = SVG_SIZE,
+        hue = hue,
+        title = escape_text(title),
+    );
+    for (index, attribute) in traits.iter().take(MAX_RENDERED_TRAITS).enumerate() {
+        svg.push_str(&format!(
+            "<text x=\"20\" y=\"{y}\" font-family=\"monospace\" font-size=\"14\" fill=\"#ffffff\">{trait_type}: {value}</text>",
+            y = 80 + LINE_HEIGHT * index as u32,
+            trait_type = escape_text(&attribute.trait_type),
+            value = escape_text(&attribute.value),
+        ));
+    }
+    svg.push_str("</svg>");
+
+    if svg.len() > MAX_SVG_BYTES {
+        return Err(StdError::generic_err(format!(
+            "Rendered svg exceeds {} bytes",
+            MAX_SVG_BYTES
+        )));
+    }
+    Ok(svg)
+}
+
+// Base64 data uri of an svg document, usable as a token uri
+pub fn svg_data_uri(svg: &str) -> String {
+    format!(
+        "data:image/svg+xml;base64,{}",
+        Binary::from(svg.as_bytes()).to_base64()
+    )
+}
+
+// Escape xml special characters and truncate long texts
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars().take(MAX_TEXT_CHARS) {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,95 @@
+use cosmwasm_std::{StdResult, Uint128, Uint256};
+use cw404_package::Pricing;
+
+pub fn is_valid_pricing(pricing: &Pricing) -> bool {
+    match pricing {
+        Pricing::Fixed { price_per_unit } => !price_per_unit.is_zero(),
+        Pricing::DutchAuction {
+            start_price,
+            floor_price,
+            decay_blocks,
+            ..
+        } => !floor_price.is_zero() && start_price >= floor_price && *decay_blocks > 0,
+        Pricing::BondingCurve { base_price, .. } => !base_price.is_zero(),
+    }
+}
+
+// Cost of amount fractions at the given height once sold fractions were sold, rounded up
+pub fn sale_cost(
+    pricing: &Pricing,
+    height: u64,
+    sold: Uint128,
+    units: Uint128,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    match pricing {
+        Pricing::Fixed { price_per_unit } => ceil_div(amount.full_mul(*price_per_unit), units),
+        Pricing::DutchAuction {
+            start_price,
+            floor_price,
+            start_height,
+            decay_blocks,
+        } => {
+            let elapsed = height.saturating_sub(*start_height).min(*decay_blocks);
+            let price =
+                *start_price - (*start_price - *floor_price).multiply_ratio(elapsed, *decay_blocks);
+            ceil_div(amount.full_mul(price), units)
+        }
+        Pricing::BondingCurve {
+            base_price,
+            increment,
+        } => {
+            let price = |index: Uint128| -> StdResult<Uint128> {
+                Ok(base_price.checked_add(increment.checked_mul(index)?)?)
+            };
+            let end = sold.checked_add(amount)?;
+            let (first, last) = (sold / units, end / units);
+            if first == last {
+                return ceil_div(amount.full_mul(price(first)?), units);
+            }
+
+            // The rest of the first unit, the whole units in between, then the start of the last
+            let head = units * (first + Uint128::one()) - sold;
+            let tail = end - units * last;
+            let whole = last - first - Uint128::one();
+            let steps = whole.checked_mul(first + Uint128::one())?.checked_add(
+                whole.checked_mul(whole.saturating_sub(Uint128::one()))? / Uint128::from(2u8),
+            )?;
+            let whole_cost = base_price
+                .checked_mul(whole)?
+                .checked_add(increment.checked_mul(steps)?)?;
+            let mut partial = head.full_mul(price(first)?);
+            if !tail.is_zero() {
+                partial += tail.full_mul(price(last)?);
+            }
+            Ok(whole_cost.checked_add(ceil_div(partial, units)?)?)
+        }
+    }
+}
+
+// Largest amount of fractions, up to max, that the payment covers
+pub fn max_affordable_amount(
+    pricing: &Pricing,
+    height: u64,
+    sold: Uint128,
+    units: Uint128,
+    payment: Uint128,
+    max: Uint128,
+) -> Uint128 {
+    // Costs grow with the amount, so search for the last affordable one
+    let (mut low, mut high) = (Uint128::zero(), max);
+    while low < high {
+        let mid = low + (high - low + Uint128::one()) / Uint128::from(2u8);
+        match sale_cost(pricing, height, sold, units, mid) {
+            Ok(cost) if cost <= payment => low = mid,
+            _ => high = mid - Uint128::one(),
+        }
+    }
+    low
+}
+
+fn ceil_div(amount: Uint256, units: Uint128) -> StdResult<Uint128> {
+    let units = Uint256::from(units);
+    let cost = (amount + units - Uint256::one()) / units;
+    Ok(Uint128::try_from(cost)?)
+}
@@ -1,9 +1,14 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, BlockInfo, Uint128};
+use cosmwasm_std::{Addr, Binary, BlockInfo, Coin, Decimal, Empty, Timestamp, Uint128};
 use cw20::AllowanceResponse;
-use cw_storage_plus::{Deque, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
-use cw_utils::Expiration;
+use cw20::Denom;
+use cw404_package::Pricing;
+use cw_storage_plus::{
+    Deque, Index, IndexList, IndexedMap, Item, Map, MultiIndex, SnapshotItem, SnapshotMap, Strategy,
+};
+use cw_utils::{Duration, Expiration};
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 #[cw_serde]
@@ -15,30 +20,362 @@ pub struct TokenInfo {
     pub admin: Addr,
     pub units: Uint128,
     pub base_token_uri: Option<String>,
+    // Set in wrapper mode, every fraction is backed one to one by this asset held by the contract
+    pub underlying: Option<Denom>,
+    // Set in wrapper mode, every unit of fractions is backed by an NFT of this collection
+    pub wrapped_collection: Option<Addr>,
 }
 
 pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
 // marketing info, logo temporary don't consider
 // cw20 info
-pub const BALANCES: Map<&Addr, Uint128> = Map::new("balance");
+// balances and total supply are snapshotted every block, so voting can use past heights
+pub const BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance",
+    "balance__checkpoints",
+    "balance__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_SUPPLY_HISTORY: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply",
+    "total_supply__checkpoints",
+    "total_supply__changelog",
+    Strategy::EveryBlock,
+);
+// Number of accounts with a non zero balance
+pub const HOLDER_COUNT: Item<u64> = Item::new("holder_count");
 pub const ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("allowance");
 
 // cw721 info
 pub const MAX_NFT_SUPPLY: Item<Uint128> = Item::new("max_nft_supply");
 pub const NFT_COUNT: Item<u64> = Item::new("nft_count");
+// Number of NFTs currently held by owners, banked ones excluded
+pub const LIVE_NFT_COUNT: Item<u64> = Item::new("live_nft_count");
+// Number of live NFTs held by each owner, owners without NFTs are removed
+pub const NFT_BALANCES: Map<&Addr, u64> = Map::new("nft_balance");
+// Ids of a wrapped collection deposited and not redeemed, live or banked
+pub const WRAPPED_NFTS: Map<&str, Empty> = Map::new("wrapped_nft");
+// NFTs protected by their owner from being banked by fractional transfers, keyed by (owner, token_id)
+pub const LOCKED_NFTS: Map<(&Addr, &str), Empty> = Map::new("locked_nft");
+// Order in which an owner's NFTs leave when a fractional transfer banks them, first leaves first
+pub const NFT_ORDER: Map<&Addr, Vec<String>> = Map::new("nft_order");
+// Staked NFTs keyed by (owner, token_id), they stay with their owner but in the contract's custody
+pub const NFT_STAKES: Map<(&Addr, &str), NftStake> = Map::new("nft_stake");
 // Stored as (granter, operator) giving operator full control over granter's account
 pub const OPERATORS: Map<(&Addr, &Addr), Expiration> = Map::new("operator");
-const INDEXES: NftIndexes = NftIndexes {
+const INDEXES: NftIndexes<Extension> = NftIndexes {
     owner: MultiIndex::new(nft_owner_idx, "token", "token_owner"),
 };
-pub const NFT_TOKENS: IndexedMap<&str, NftInfo, NftIndexes> = IndexedMap::new("token", INDEXES);
+pub const NFT_TOKENS: IndexedMap<&str, NftInfo<Extension>, NftIndexes<Extension>> =
+    IndexedMap::new("token", INDEXES);
+// Extensions of banked (or not yet minted) tokens keyed by token_id, so metadata is restored
+// when the id is minted again from the bank
+pub const BANKED_EXTENSIONS: Map<&str, Extension> = Map::new("banked_extension");
+// cw2981 royalties, collection wide and per token overrides keyed by token_id
+// overrides are kept apart from NftInfo so they survive the token being banked and re-minted
+pub const ROYALTY_INFO: Item<RoyaltyInfo> = Item::new("royalty_info");
+pub const TOKEN_ROYALTY_INFO: Map<&str, RoyaltyInfo> = Map::new("token_royalty_info");
+
+// deterministic trait generation, layers uploaded by the admin and a seed fixed at instantiate
+pub const TRAIT_LAYERS: Item<Vec<TraitLayer>> = Item::new("trait_layers");
+pub const COLLECTION_SEED: Item<Binary> = Item::new("collection_seed");
+// Rarity score of each generated token, computed once when the id is first minted
+pub const TOKEN_RARITY: Map<&str, Rarity> = Map::new("token_rarity");
+
+// delayed reveal, token uris resolve to a placeholder until the admin reveals
+pub const REVEAL_INFO: Item<RevealInfo> = Item::new("reveal_info");
+
+// token uris are built at query time from the template, unless a token has an override
+pub const METADATA_CONFIG: Item<MetadataConfig> = Item::new("metadata_config");
+pub const TOKEN_URI_OVERRIDES: Map<&str, String> = Map::new("token_uri_override");
 
-// nft queue using deque
-pub const DEQUE_NFT: Deque<Uint128> = Deque::new("deque_nft");
+// nft queue using deque, ids are kept as strings so wrapped collections keep their own ids.
+// They serialize the same way the former Uint128 entries did.
+pub const DEQUE_NFT: Deque<String> = Deque::new("deque_nft");
 pub const CW721_TRANSFER_EXEMPT: Map<&Addr, bool> = Map::new("cw721_traansfer_exempt");
 
+// launch info, trading gate and anti-bot cooldown
+pub const LAUNCH_INFO: Item<LaunchInfo> = Item::new("launch_info");
+// Last block height at which a non exempt address received tokens during the cooldown window
+pub const LAST_TRANSFER_HEIGHT: Map<&Addr, u64> = Map::new("last_transfer_height");
+
+// buy / sell / transfer fee config
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
+
+#[cw_serde]
+pub struct FeeConfig {
+    // Fee rates in basis points, buy is from an exempt address, sell is to an exempt address
+    pub buy_fee_bps: u16,
+    pub sell_fee_bps: u16,
+    pub transfer_fee_bps: u16,
+    // Receiver of the collected fees
+    pub treasury: Addr,
+}
+
+// nft staking, rewards accrue per staked NFT per block
+pub const NFT_STAKING_CONFIG: Item<NftStakingConfig> = Item::new("nft_staking_config");
+pub const NFT_REWARD_INDEX: Item<RewardIndex> = Item::new("nft_reward_index");
+// Rewards accrued by each staker and not claimed yet
+pub const NFT_STAKING_REWARDS: Map<&Addr, Uint128> = Map::new("nft_staking_reward");
+
+#[cw_serde]
+pub struct NftStakingConfig {
+    pub reward_denom: Denom,
+    // Rewards of a single staked NFT per block
+    pub reward_per_block: Uint128,
+    // How long an unstaked NFT stays in custody
+    pub unbonding_period: Duration,
+}
+
+#[cw_serde]
+pub struct RewardIndex {
+    // Rewards accrued by a single staked NFT since staking was configured, up to height
+    pub index: Uint128,
+    pub height: u64,
+}
+
+impl RewardIndex {
+    pub fn current(&self, reward_per_block: Uint128, block: &BlockInfo) -> Uint128 {
+        let blocks = block.height.saturating_sub(self.height);
+        self.index + reward_per_block * Uint128::from(blocks)
+    }
+}
+
+#[cw_serde]
+pub struct NftStake {
+    // Reward index when the stake last accrued
+    pub reward_index: Uint128,
+    // Set on unstake, the NFT leaves custody once it expires
+    pub unbonding: Option<Expiration>,
+}
+
+impl NftStake {
+    pub fn in_custody(&self, block: &BlockInfo) -> bool {
+        match self.unbonding {
+            Some(unbonding) => !unbonding.is_expired(block),
+            None => true,
+        }
+    }
+
+    // Unbonding stakes no longer earn, their rewards accrued on unstake
+    pub fn pending_rewards(&self, reward_index: Uint128) -> Uint128 {
+        match self.unbonding {
+            Some(_) => Uint128::zero(),
+            None => reward_index - self.reward_index,
+        }
+    }
+}
+
+// fraction staking, rewards are shared pro rata between the staked fractions
+// Staked fractions are held in the contract's balance
+pub const FRACTION_STAKING_CONFIG: Item<FractionStakingConfig> =
+    Item::new("fraction_staking_config");
+pub const FRACTION_REWARD_INDEX: Item<FractionRewardIndex> = Item::new("fraction_reward_index");
+pub const FRACTION_STAKES: Map<&Addr, FractionStake> = Map::new("fraction_stake");
+
+#[cw_serde]
+pub struct FractionStakingConfig {
+    pub reward_denom: Denom,
+    // Rewards shared by all staked fractions per block
+    pub reward_per_block: Uint128,
+}
+
+#[cw_serde]
+pub struct FractionRewardIndex {
+    // Rewards accrued by a single staked fraction, up to height
+    pub index: Decimal,
+    pub height: u64,
+    pub total_staked: Uint128,
+}
+
+impl FractionRewardIndex {
+    pub fn current(&self, reward_per_block: Uint128, block: &BlockInfo) -> Decimal {
+        let blocks = block.height.saturating_sub(self.height);
+        if self.total_staked.is_zero() || blocks == 0 {
+            return self.index;
+        }
+        self.index
+            + Decimal::from_ratio(reward_per_block * Uint128::from(blocks), self.total_staked)
+    }
+}
+
+#[cw_serde]
+pub struct FractionStake {
+    pub amount: Uint128,
+    // Reward index when the stake last accrued
+    pub reward_index: Decimal,
+    // Rewards accrued and not claimed yet
+    pub pending_rewards: Uint128,
+}
+
+impl FractionStake {
+    pub fn accrue(&mut self, reward_index: Decimal) {
+        self.pending_rewards += self.amount * (reward_index - self.reward_index);
+        self.reward_index = reward_index;
+    }
+}
+
+// native coin sale of newly minted supply
+pub const SALE_CONFIG: Item<SaleConfig> = Item::new("sale_config");
+// Fractions bought by each wallet
+pub const SALE_PURCHASES: Map<&Addr, Uint128> = Map::new("sale_purchase");
+
+#[cw_serde]
+pub struct SaleConfig {
+    pub denom: String,
+    pub pricing: Pricing,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    // Caps in fractions
+    pub per_wallet_cap: Uint128,
+    pub total_cap: Uint128,
+    // Fractions sold so far
+    pub sold: Uint128,
+    // Coins received and not withdrawn yet
+    pub proceeds: Uint128,
+}
+
+impl SaleConfig {
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        block.time >= self.start_time && block.time < self.end_time && self.sold < self.total_cap
+    }
+}
+
+// whitelist phases, each with a merkle root over (address, allocation) pairs
+pub const WHITELIST_PHASES: Map<u64, WhitelistPhase> = Map::new("whitelist_phase");
+// Fractions claimed or bought by each address in each phase
+pub const WHITELIST_CLAIMED: Map<(u64, &Addr), Uint128> = Map::new("whitelist_claimed");
+
+#[cw_serde]
+pub struct WhitelistPhase {
+    pub merkle_root: Binary,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    // Price of a whole unit, airdrop phases have none
+    pub price_per_unit: Option<Coin>,
+    // Coins received and not withdrawn yet
+    pub proceeds: Uint128,
+}
+
+impl WhitelistPhase {
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        block.time >= self.start_time && block.time < self.end_time
+    }
+}
+
+// vesting schedules keyed by beneficiary, one each, the locked amount is held in the contract's
+// balance
+pub const VESTING_SCHEDULES: Map<&Addr, VestingSchedule> = Map::new("vesting_schedule");
+
+#[cw_serde]
+pub struct VestingSchedule {
+    pub total: Uint128,
+    pub claimed: Uint128,
+    pub start_height: u64,
+    pub start_time: Timestamp,
+    // Nothing vests before the cliff, then vesting is linear until the end of the duration.
+    // Both are in heights or both in time.
+    pub cliff: Duration,
+    pub duration: Duration,
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        match (self.cliff, self.duration) {
+            (Duration::Height(cliff), Duration::Height(duration))
+            | (Duration::Time(cliff), Duration::Time(duration)) => {
+                !self.total.is_zero() && duration > 0 && cliff <= duration
+            }
+            _ => false,
+        }
+    }
+
+    pub fn vested(&self, block: &BlockInfo) -> Uint128 {
+        let (elapsed, cliff, duration) = match (self.cliff, self.duration) {
+            (Duration::Height(cliff), Duration::Height(duration)) => (
+                block.height.saturating_sub(self.start_height),
+                cliff,
+                duration,
+            ),
+            (Duration::Time(cliff), Duration::Time(duration)) => (
+                block
+                    .time
+                    .seconds()
+                    .saturating_sub(self.start_time.seconds()),
+                cliff,
+                duration,
+            ),
+            _ => return Uint128::zero(),
+        };
+        if elapsed < cliff {
+            Uint128::zero()
+        } else if elapsed >= duration {
+            self.total
+        } else {
+            self.total.multiply_ratio(elapsed, duration)
+        }
+    }
+}
+
+// Optional fee charged on reroll, paid to the fee treasury
+pub const REROLL_FEE: Item<RerollFee> = Item::new("reroll_fee");
+
+#[cw_serde]
+pub enum RerollFee {
+    // Paid in native coin sent along with the message
+    Native(Coin),
+    // Paid in fractions from the caller's balance, must stay below one unit
+    Fractions(Uint128),
+}
+
+#[cw_serde]
+pub struct MetadataConfig {
+    // Template of token uris, {base} is replaced by the base token uri and {id} by the token id
+    pub token_uri_template: String,
+    // Once frozen, token uris and metadata can not be changed anymore
+    pub frozen: bool,
+}
+
+#[cw_serde]
+pub struct RevealInfo {
+    pub revealed: bool,
+    // Uri reported by every token before reveal
+    pub placeholder_uri: Option<String>,
+    // sha256 of the seed committed before reveal, the seed derives the id shuffle offset
+    pub seed_hash: Option<Binary>,
+    // Offset applied to token ids when building revealed uris
+    pub offset: Option<u64>,
+    // Nft supply at reveal, only ids up to it are shuffled so later mints do not remap them
+    pub revealed_supply: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct LaunchInfo {
+    pub trading_enabled: bool,
+    // Height from which non exempt addresses can transfer, set when trading is enabled
+    pub launch_height: Option<u64>,
+    // Number of blocks after launch in which a non exempt address may receive at most once per block
+    pub cooldown_blocks: u64,
+}
+
+impl LaunchInfo {
+    pub fn is_launched(&self, block: &BlockInfo) -> bool {
+        match self.launch_height {
+            Some(height) => self.trading_enabled && block.height >= height,
+            None => false,
+        }
+    }
+
+    pub fn in_cooldown(&self, block: &BlockInfo) -> bool {
+        match self.launch_height {
+            Some(height) => block.height < height.saturating_add(self.cooldown_blocks),
+            None => false,
+        }
+    }
+}
+
 #[derive(JsonSchema, Serialize, Deserialize, PartialEq, Clone, Debug)]
-pub struct NftInfo {
+pub struct NftInfo<T> {
     // The owner of newly minted Nft
     pub owner: Addr,
     // Approvals are stored here, as we clear them all upon transfer and cannot accumulate much
@@ -47,23 +384,80 @@ pub struct NftInfo {
     // Should point to a JSON file that conforms to the ERC721
     // Metadata JSON Schema
     pub token_uri: Option<String>,
+    // You can add any custom metadata here when you extend cw404
+    pub extension: T,
 }
 
-pub struct NftIndexes<'a> {
-    pub owner: MultiIndex<'a, Addr, NftInfo, String>,
+// On-chain metadata in the style of cw721-metadata-onchain
+#[cw_serde]
+#[derive(Default)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub attributes: Option<Vec<Trait>>,
+    pub animation_url: Option<String>,
+}
+
+#[cw_serde]
+pub struct Trait {
+    pub display_type: Option<String>,
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[cw_serde]
+pub struct TraitLayer {
+    pub trait_type: String,
+    pub options: Vec<TraitOption>,
 }
 
-impl<'a> IndexList<NftInfo> for NftIndexes<'a> {
-    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<NftInfo>> + '_> {
-        let v: Vec<&dyn Index<NftInfo>> = vec![&self.owner];
+#[cw_serde]
+pub struct TraitOption {
+    pub value: String,
+    // Relative weight of this option inside its layer
+    pub weight: u32,
+}
+
+#[cw_serde]
+pub struct Rarity {
+    // Sum over layers of the inverse probability of the picked option
+    pub score: Decimal,
+    pub tier: String,
+}
+
+// Swap this alias to plug a custom metadata schema into the collection
+pub type Extension = Option<Metadata>;
+
+pub struct NftIndexes<'a, T>
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    pub owner: MultiIndex<'a, Addr, NftInfo<T>, String>,
+}
+
+impl<'a, T> IndexList<NftInfo<T>> for NftIndexes<'a, T>
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<NftInfo<T>>> + '_> {
+        let v: Vec<&dyn Index<NftInfo<T>>> = vec![&self.owner];
         Box::new(v.into_iter())
     }
 }
 
-pub fn nft_owner_idx(_pk: &[u8], d: &NftInfo) -> Addr {
+pub fn nft_owner_idx<T>(_pk: &[u8], d: &NftInfo<T>) -> Addr {
     d.owner.clone()
 }
 
+#[cw_serde]
+pub struct RoyaltyInfo {
+    // Address royalties are paid to
+    pub payment_address: Addr,
+    // Percentage of the sale price, 0-100
+    pub royalty_percentage: u64,
+}
+
 #[derive(JsonSchema, Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Approval {
     // Account can transfer / send nft
@@ -1,21 +1,76 @@
 use std::vec;
 
-use cosmwasm_std::{Addr, Uint128};
-use cw20::{BalanceResponse, Cw20Coin};
-use cw721::{NumTokensResponse, OwnerOfResponse};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    coin, coins, from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+use cw20::{AllAccountsResponse, BalanceResponse, Cw20Coin, Cw20QueryMsg, Cw20ReceiveMsg, Denom};
+use cw721::{
+    AllNftInfoResponse, Cw721QueryMsg, Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse,
+    OwnerOfResponse,
+};
 use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_storage_plus::Map;
+use cw_utils::{Duration, PaymentError};
+use sha2::{Digest, Sha256};
 
-use cw404_package::{Cw721TransferExemptResponse, MaxNftSupplyRespone, TokenInfoResponse};
+use cw404_package::{
+    AccountMismatch, BankSizeResponse, BankedNftsResponse, CheckInvariantsResponse,
+    CheckRoyaltiesResponse, CurrentPriceResponse, Cw721TransferExemptResponse,
+    Erc721BalanceOfResponse, FeeConfigResponse, FractionStakeResponse,
+    FractionStakingConfigResponse, HolderCountResponse, IsRevealedResponse, LaunchInfoResponse,
+    LockedNftsResponse, MaxNftSupplyRespone, NextFromBankResponse, NftBalanceResponse,
+    NftHoldersResponse, NftOrderResponse, NftStakesResponse, PendingNftRewardsResponse, Pricing,
+    RarityResponse, RenderSvgResponse, RoyaltiesInfoResponse, SaleInfoResponse, TokenInfoResponse,
+    TotalSupplyAtHeightResponse, VestingResponse, WhitelistClaimedResponse,
+};
 
-use crate::contract::{execute, instantiate, query};
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::contract::{execute, instantiate, migrate, query};
+use crate::error::ContractError;
+use crate::merkle::merkle_leaf;
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RoyaltyInfoMsg, VestingScheduleMsg,
+};
+use crate::query::{query_nft_balance, query_nft_info};
+use crate::render::{render_svg, MAX_SVG_BYTES};
+use crate::sale::sale_cost;
+use crate::state::{
+    Extension, Metadata, NftInfo, RerollFee, TokenInfo, Trait, TraitLayer, TraitOption,
+    CW721_TRANSFER_EXEMPT, MAX_NFT_SUPPLY, NFT_COUNT, NFT_TOKENS, TOKEN_INFO,
+};
+use crate::traits::derive_traits;
 
 pub struct InstantiateResponse {
     pub app: App,
     pub address: Addr,
 }
 
+fn default_instantiate_msg(initial_balance_amount: Uint128) -> InstantiateMsg {
+    InstantiateMsg {
+        name: "Orai Pandora".to_string(),
+        symbol: "OPAN".to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: "admin".to_string(),
+            amount: initial_balance_amount,
+        }],
+        admin: "admin".to_string(),
+        base_token_uri: Option::Some("https://example.com/token/".to_string()),
+        trait_layers: None,
+        placeholder_token_uri: None,
+        vesting_schedules: None,
+        underlying: None,
+        wrapped_collection: None,
+    }
+}
+
 fn intantisate_contract(initial_balance_amount: Uint128) -> InstantiateResponse {
+    intantisate_contract_with_msg(default_instantiate_msg(initial_balance_amount))
+}
+
+fn intantisate_contract_with_msg(msg: InstantiateMsg) -> InstantiateResponse {
     let mut app = App::default();
     let code = ContractWrapper::new(execute, instantiate, query);
     let code_id = app.store_code(Box::new(code));
@@ -24,17 +79,7 @@ fn intantisate_contract(initial_balance_amount: Uint128) -> InstantiateResponse
         .instantiate_contract(
             code_id,
             Addr::unchecked("admin"),
-            &InstantiateMsg {
-                name: "Orai Pandora".to_string(),
-                symbol: "OPAN".to_string(),
-                decimals: 6,
-                initial_balances: vec![Cw20Coin {
-                    address: "admin".to_string(),
-                    amount: initial_balance_amount,
-                }],
-                admin: "admin".to_string(),
-                base_token_uri: Option::Some("https://example.com/token/".to_string()),
-            },
+            &msg,
             &[],
             "cw404 contract",
             None,
@@ -83,7 +128,9 @@ pub fn initial_token_info() {
             decimals: 6,
             total_supply: Uint128::from(10000u128) * Uint128::from(10u128).pow(6),
             admin: Addr::unchecked("admin"),
-            units: Uint128::from(10u128.pow(6))
+            units: Uint128::from(10u128.pow(6)),
+            underlying: None,
+            wrapped_collection: None,
         }
     );
 }
@@ -225,3 +272,4077 @@ pub fn tranfer_test_right_nft_owner() {
         }
     );
 }
+
+#[test]
+pub fn transfer_before_launch_only_exempt() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(100u128),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "bot".to_string(),
+                amount: Uint128::from(10u128),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TradingNotEnabled {}
+    );
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address,
+            &ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+}
+
+#[test]
+pub fn transfer_cooldown_after_launch() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let launch_height = instantiate_resp.app.block_info().height;
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 2,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let resp: LaunchInfoResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::LaunchInfo {})
+        .unwrap();
+    assert_eq!(
+        resp,
+        LaunchInfoResponse {
+            trading_enabled: true,
+            launch_height: Some(launch_height),
+            cooldown_blocks: 2,
+        }
+    );
+
+    let transfer = ExecuteMsg::Transfer {
+        recipient: "bot".to_string(),
+        amount: Uint128::from(100u128),
+    };
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &transfer,
+            &[],
+        )
+        .unwrap();
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &transfer,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TransferCooldown {
+            address: "bot".to_string()
+        }
+    );
+
+    // next block inside the cooldown window
+    instantiate_resp.app.update_block(|block| block.height += 1);
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &transfer,
+            &[],
+        )
+        .unwrap();
+
+    // after the cooldown window
+    instantiate_resp.app.update_block(|block| block.height += 1);
+    for _i in 0..2 {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &transfer,
+                &[],
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+pub fn transfer_fee_to_treasury() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetFeeConfig {
+                buy_fee_bps: 300,
+                sell_fee_bps: 2000,
+                transfer_fee_bps: 0,
+                treasury: "treasury".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeTooHigh { max_bps: 1000 }
+    );
+
+    for msg in [
+        ExecuteMsg::SetFeeConfig {
+            buy_fee_bps: 300,
+            sell_fee_bps: 500,
+            transfer_fee_bps: 100,
+            treasury: "treasury".to_string(),
+        },
+        ExecuteMsg::SetCw721TransferExempt {
+            address: "pair".to_string(),
+            state: true,
+        },
+        ExecuteMsg::EnableTrading {
+            launch_height: None,
+            cooldown_blocks: 0,
+        },
+        // admin transfers are not taxed
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(2u128) * units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp: FeeConfigResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::FeeConfig {})
+        .unwrap();
+    assert_eq!(
+        resp,
+        FeeConfigResponse {
+            buy_fee_bps: 300,
+            sell_fee_bps: 500,
+            transfer_fee_bps: 100,
+            treasury: Addr::unchecked("treasury"),
+        }
+    );
+
+    // sell one whole unit to the pair
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "pair".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+
+    for (address, balance) in [
+        ("huy", units),
+        ("pair", Uint128::from(950_000u128)),
+        ("treasury", Uint128::from(50_000u128)),
+    ] {
+        let resp: BalanceResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                instantiate_resp.address.clone(),
+                &QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp, BalanceResponse { balance });
+    }
+    // huy's NFT of the unit sold is banked, neither the pair nor the treasury hold one
+    for (address, nft_count) in [("huy", 1), ("pair", 0), ("treasury", 0)] {
+        let resp: NftBalanceResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                instantiate_resp.address.clone(),
+                &QueryMsg::NftBalance {
+                    owner: address.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.count, nft_count);
+    }
+
+    // buy back from the pair, the recipient's NFTs reflect the net amount received
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("pair"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(950_000u128),
+            },
+            &[],
+        )
+        .unwrap();
+    let resp: BalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        BalanceResponse {
+            balance: units + Uint128::from(921_500u128)
+        }
+    );
+
+    // taxed trades crossing a whole unit move NFTs for the net amounts only, the fees
+    // leave the treasury below a whole unit
+    for (sender, recipient, amount, holdings) in [
+        // huy drops below two units, the NFT is banked
+        (
+            "huy",
+            "pair",
+            units,
+            [
+                ("huy", Uint128::from(921_500u128), 0),
+                ("pair", Uint128::from(950_000u128), 0),
+                ("treasury", Uint128::from(128_500u128), 0),
+            ],
+        ),
+        // the net amount takes huy back over one unit, the NFT is retrieved
+        (
+            "pair",
+            "huy",
+            Uint128::from(950_000u128),
+            [
+                ("huy", Uint128::from(1_843_000u128), 1),
+                ("pair", Uint128::zero(), 0),
+                ("treasury", Uint128::from(157_000u128), 0),
+            ],
+        ),
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                instantiate_resp.address.clone(),
+                &ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount,
+                },
+                &[],
+            )
+            .unwrap();
+        for (address, balance, nft_count) in holdings {
+            let resp: BalanceResponse = instantiate_resp
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    instantiate_resp.address.clone(),
+                    &QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp, BalanceResponse { balance });
+            let resp: NftBalanceResponse = instantiate_resp
+                .app
+                .wrap()
+                .query_wasm_smart(
+                    instantiate_resp.address.clone(),
+                    &QueryMsg::NftBalance {
+                        owner: address.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.count, nft_count);
+        }
+    }
+}
+
+#[test]
+pub fn royalty_info_with_token_override() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let royalty_info = |app: &App, address: &Addr, token_id: &str| -> RoyaltiesInfoResponse {
+        app.wrap()
+            .query_wasm_smart(
+                address.clone(),
+                &QueryMsg::RoyaltyInfo {
+                    token_id: token_id.to_string(),
+                    sale_price: Uint128::from(1000u128),
+                },
+            )
+            .unwrap()
+    };
+
+    assert_eq!(
+        royalty_info(&instantiate_resp.app, &instantiate_resp.address, "1"),
+        RoyaltiesInfoResponse {
+            address: "".to_string(),
+            royalty_amount: Uint128::zero()
+        }
+    );
+    let resp: CheckRoyaltiesResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::CheckRoyalties {},
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        CheckRoyaltiesResponse {
+            royalty_payments: true
+        }
+    );
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetRoyaltyInfo {
+                royalty: Some(RoyaltyInfoMsg {
+                    payment_address: "artist".to_string(),
+                    royalty_percentage: 101,
+                }),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRoyaltyPercentage {}
+    );
+
+    for msg in [
+        ExecuteMsg::SetRoyaltyInfo {
+            royalty: Some(RoyaltyInfoMsg {
+                payment_address: "artist".to_string(),
+                royalty_percentage: 5,
+            }),
+        },
+        ExecuteMsg::SetTokenRoyaltyInfo {
+            token_id: "7".to_string(),
+            royalty: Some(RoyaltyInfoMsg {
+                payment_address: "collab".to_string(),
+                royalty_percentage: 10,
+            }),
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    assert_eq!(
+        royalty_info(&instantiate_resp.app, &instantiate_resp.address, "1"),
+        RoyaltiesInfoResponse {
+            address: "artist".to_string(),
+            royalty_amount: Uint128::from(50u128)
+        }
+    );
+    assert_eq!(
+        royalty_info(&instantiate_resp.app, &instantiate_resp.address, "7"),
+        RoyaltiesInfoResponse {
+            address: "collab".to_string(),
+            royalty_amount: Uint128::from(100u128)
+        }
+    );
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetTokenRoyaltyInfo {
+                token_id: "10001".to_string(),
+                royalty: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTokenId {
+            token_id: "10001".to_string()
+        }
+    );
+
+    // removing the override falls back to the collection wide royalty
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetTokenRoyaltyInfo {
+                token_id: "7".to_string(),
+                royalty: None,
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        royalty_info(&instantiate_resp.app, &instantiate_resp.address, "7"),
+        RoyaltiesInfoResponse {
+            address: "artist".to_string(),
+            royalty_amount: Uint128::from(50u128)
+        }
+    );
+}
+
+#[test]
+pub fn nft_metadata_survives_bank() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let extension = Some(Metadata {
+        name: Some("Pandora #1".to_string()),
+        description: None,
+        image: Some("ipfs://pandora/1.png".to_string()),
+        attributes: Some(vec![Trait {
+            display_type: None,
+            trait_type: "eyes".to_string(),
+            value: "laser".to_string(),
+        }]),
+        animation_url: None,
+    });
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetNftMetadata {
+                token_id: "1".to_string(),
+                extension: extension.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // set before the token is minted, then mint it to huy
+    for msg in [
+        ExecuteMsg::SetNftMetadata {
+            token_id: "1".to_string(),
+            extension: extension.clone(),
+        },
+        ExecuteMsg::SetCw721TransferExempt {
+            address: "pair".to_string(),
+            state: true,
+        },
+        ExecuteMsg::EnableTrading {
+            launch_height: None,
+            cooldown_blocks: 0,
+        },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp: NftInfoResponse<Extension> = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::NftInfo {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        NftInfoResponse {
+            token_uri: Some("https://example.com/token/1".to_string()),
+            extension: extension.clone(),
+        }
+    );
+
+    // bank the token, then mint it again to another owner
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "pair".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let resp: AllNftInfoResponse<Extension> = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::AllNftInfo {
+                token_id: "1".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        AllNftInfoResponse {
+            access: OwnerOfResponse {
+                owner: "bob".to_string(),
+                approvals: vec![]
+            },
+            info: NftInfoResponse {
+                token_uri: Some("https://example.com/token/1".to_string()),
+                extension,
+            }
+        }
+    );
+}
+
+fn trait_layers() -> Vec<TraitLayer> {
+    vec![
+        TraitLayer {
+            trait_type: "background".to_string(),
+            options: vec![TraitOption {
+                value: "blue".to_string(),
+                weight: 1,
+            }],
+        },
+        TraitLayer {
+            trait_type: "eyes".to_string(),
+            options: vec![
+                TraitOption {
+                    value: "laser".to_string(),
+                    weight: 1,
+                },
+                TraitOption {
+                    value: "normal".to_string(),
+                    weight: 99,
+                },
+            ],
+        },
+    ]
+}
+
+#[test]
+pub fn derive_traits_is_deterministic() {
+    let layers = trait_layers();
+    let (traits, rarity) = derive_traits(b"seed", Uint128::from(1u128), &layers);
+    assert_eq!(
+        (traits.clone(), rarity.clone()),
+        derive_traits(b"seed", Uint128::from(1u128), &layers)
+    );
+    assert_eq!(traits.len(), 2);
+    assert_eq!(traits[0].value, "blue".to_string());
+
+    // over many ids both eyes show up, laser being the rare one
+    let lasers = (1..=1000u128)
+        .filter(|id| {
+            let (traits, rarity) = derive_traits(b"seed", Uint128::from(*id), &layers);
+            let is_laser = traits[1].value == "laser";
+            assert_eq!(
+                rarity.tier,
+                if is_laser { "epic" } else { "common" }.to_string()
+            );
+            is_laser
+        })
+        .count();
+    assert!(lasers > 0 && lasers < 50);
+}
+
+#[test]
+pub fn mint_generates_traits() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetTraitLayers {
+                layers: vec![TraitLayer {
+                    trait_type: "eyes".to_string(),
+                    options: vec![],
+                }],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTraitLayer {
+            trait_type: "eyes".to_string()
+        }
+    );
+
+    for msg in [
+        ExecuteMsg::SetTraitLayers {
+            layers: trait_layers(),
+        },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(3u128) * units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    for token_id in ["1", "2", "3"] {
+        let nft_info: NftInfoResponse<Extension> = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                instantiate_resp.address.clone(),
+                &QueryMsg::NftInfo {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap();
+        let attributes = nft_info.extension.unwrap().attributes.unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[0].trait_type, "background".to_string());
+        assert_eq!(attributes[1].trait_type, "eyes".to_string());
+
+        let rarity: RarityResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                instantiate_resp.address.clone(),
+                &QueryMsg::RarityOf {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap();
+        let is_laser = attributes[1].value == "laser";
+        assert_eq!(
+            rarity.tier,
+            if is_laser { "epic" } else { "common" }.to_string()
+        );
+    }
+}
+
+#[test]
+pub fn delayed_reveal_with_committed_seed() {
+    let mut instantiate_resp = intantisate_contract_with_msg(InstantiateMsg {
+        placeholder_token_uri: Some("https://example.com/hidden.json".to_string()),
+        trait_layers: Some(trait_layers()),
+        ..default_instantiate_msg(Uint128::from(10u128))
+    });
+    let units = Uint128::from(10u128.pow(6));
+    let nft_info = |app: &App, address: &Addr, token_id: &str| -> NftInfoResponse<Extension> {
+        app.wrap()
+            .query_wasm_smart(
+                address.clone(),
+                &QueryMsg::NftInfo {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap()
+    };
+    let token_uri = |app: &App, address: &Addr, token_id: &str| -> Option<String> {
+        nft_info(app, address, token_id).token_uri
+    };
+
+    let seed = Binary::from(b"pandora seed".to_vec());
+    let seed_hash = Binary::from(Sha256::digest(seed.as_slice()).to_vec());
+    for msg in [
+        ExecuteMsg::CommitReveal { seed_hash },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(2u128) * units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp: IsRevealedResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::IsRevealed {})
+        .unwrap();
+    assert_eq!(resp, IsRevealedResponse { revealed: false });
+    assert_eq!(
+        token_uri(&instantiate_resp.app, &instantiate_resp.address, "1"),
+        Some("https://example.com/hidden.json".to_string())
+    );
+    // queries do not show traits and rarity until reveal
+    assert_eq!(
+        nft_info(&instantiate_resp.app, &instantiate_resp.address, "1").extension,
+        None
+    );
+    instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart::<RarityResponse>(
+            instantiate_resp.address.clone(),
+            &QueryMsg::RarityOf {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap_err();
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Reveal {
+                seed: Some(Binary::from(b"other seed".to_vec())),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRevealSeed {}
+    );
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Reveal {
+                seed: Some(seed.clone()),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let hash = Sha256::new()
+        .chain_update(seed.as_slice())
+        .chain_update(b"offset")
+        .finalize();
+    let offset = u128::from_be_bytes(hash[0..16].try_into().unwrap()) % 10;
+    assert!(
+        nft_info(&instantiate_resp.app, &instantiate_resp.address, "1")
+            .extension
+            .is_some()
+    );
+    for id in [1u128, 2u128] {
+        assert_eq!(
+            token_uri(
+                &instantiate_resp.app,
+                &instantiate_resp.address,
+                &id.to_string()
+            ),
+            Some(format!(
+                "https://example.com/token/{}",
+                (id - 1 + offset) % 10 + 1
+            ))
+        );
+    }
+
+    // minting after reveal leaves revealed uris in place, later ids keep their own
+    let now = instantiate_resp.app.block_info().time;
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("huy"), coins(900, "orai"))
+        })
+        .unwrap();
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetSaleConfig {
+                denom: "orai".to_string(),
+                pricing: Pricing::Fixed {
+                    price_per_unit: Uint128::from(100u128),
+                },
+                start_time: now,
+                end_time: now.plus_seconds(1000),
+                per_wallet_cap: Uint128::from(9u128) * units,
+                total_cap: Uint128::from(9u128) * units,
+            },
+            &[],
+        )
+        .unwrap();
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::BuyWithNative { amount: None },
+            &coins(900, "orai"),
+        )
+        .unwrap();
+    for id in [1u128, 2u128] {
+        assert_eq!(
+            token_uri(
+                &instantiate_resp.app,
+                &instantiate_resp.address,
+                &id.to_string()
+            ),
+            Some(format!(
+                "https://example.com/token/{}",
+                (id - 1 + offset) % 10 + 1
+            ))
+        );
+    }
+    assert_eq!(
+        token_uri(&instantiate_resp.app, &instantiate_resp.address, "11"),
+        Some("https://example.com/token/11".to_string())
+    );
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address,
+            &ExecuteMsg::Reveal { seed: Some(seed) },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AlreadyRevealed {}
+    );
+}
+
+#[test]
+pub fn templated_token_uri_update_and_freeze() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let token_uri = |app: &App, address: &Addr, token_id: &str| -> Option<String> {
+        let resp: NftInfoResponse<Extension> = app
+            .wrap()
+            .query_wasm_smart(
+                address.clone(),
+                &QueryMsg::NftInfo {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap();
+        resp.token_uri
+    };
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::UpdateBaseTokenUri {
+                base_token_uri: Some("ipfs://new/".to_string()),
+                token_uri_template: Some("{base}.json".to_string()),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTokenUriTemplate {}
+    );
+
+    for msg in [
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(2u128) * Uint128::from(10u128).pow(6),
+        },
+        ExecuteMsg::UpdateBaseTokenUri {
+            base_token_uri: Some("ipfs://new/".to_string()),
+            token_uri_template: Some("{base}{id}.json".to_string()),
+        },
+        ExecuteMsg::SetTokenUri {
+            token_id: "2".to_string(),
+            token_uri: Some("ar://special".to_string()),
+        },
+        ExecuteMsg::FreezeMetadata {},
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+    assert_eq!(
+        token_uri(&instantiate_resp.app, &instantiate_resp.address, "1"),
+        Some("ipfs://new/1.json".to_string())
+    );
+    assert_eq!(
+        token_uri(&instantiate_resp.app, &instantiate_resp.address, "2"),
+        Some("ar://special".to_string())
+    );
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address,
+            &ExecuteMsg::SetTokenUri {
+                token_id: "2".to_string(),
+                token_uri: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MetadataFrozen {}
+    );
+}
+
+#[test]
+pub fn migrate_strips_token_uris() {
+    let mut deps = mock_dependencies();
+    let units = Uint128::from(10u128.pow(6));
+    // state of the previous version, only the keys it wrote
+    TOKEN_INFO
+        .save(
+            deps.as_mut().storage,
+            &TokenInfo {
+                name: "Orai Pandora".to_string(),
+                symbol: "OPAN".to_string(),
+                decimals: 6,
+                total_supply: Uint128::from(10u128) * units,
+                admin: Addr::unchecked("admin"),
+                units,
+                base_token_uri: Some("https://example.com/token/".to_string()),
+                underlying: None,
+                wrapped_collection: None,
+            },
+        )
+        .unwrap();
+    let balances: Map<&Addr, Uint128> = Map::new("balance");
+    balances
+        .save(
+            deps.as_mut().storage,
+            &Addr::unchecked("admin"),
+            &(Uint128::from(9u128) * units),
+        )
+        .unwrap();
+    balances
+        .save(deps.as_mut().storage, &Addr::unchecked("huy"), &units)
+        .unwrap();
+    MAX_NFT_SUPPLY
+        .save(deps.as_mut().storage, &Uint128::from(10u128))
+        .unwrap();
+    NFT_COUNT.save(deps.as_mut().storage, &1).unwrap();
+    CW721_TRANSFER_EXEMPT
+        .save(deps.as_mut().storage, &Addr::unchecked("admin"), &true)
+        .unwrap();
+    // token minted by a previous version, with its uri persisted
+    NFT_TOKENS
+        .save(
+            deps.as_mut().storage,
+            "1",
+            &NftInfo {
+                owner: Addr::unchecked("huy"),
+                approvals: vec![],
+                token_uri: Some("https://example.com/token/1".to_string()),
+                extension: None,
+            },
+        )
+        .unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let nft_info = NFT_TOKENS.load(deps.as_ref().storage, "1").unwrap();
+    assert_eq!(nft_info.token_uri, None);
+    let resp = query_nft_balance(deps.as_ref(), "huy".to_string()).unwrap();
+    assert_eq!(resp, NftBalanceResponse { count: 1 });
+    let resp = query_nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(
+        resp.token_uri,
+        Some("https://example.com/token/1".to_string())
+    );
+
+    // the upgraded token keeps trading
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: units,
+        },
+    )
+    .unwrap();
+    let resp = query_nft_balance(deps.as_ref(), "huy".to_string()).unwrap();
+    assert_eq!(resp, NftBalanceResponse { count: 2 });
+    let resp: TotalSupplyAtHeightResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TotalSupplyAtHeight {
+                height: mock_env().block.height + 1,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(resp.total_supply, Uint128::from(10u128) * units);
+}
+
+#[test]
+pub fn render_svg_escapes_and_limits() {
+    let traits = (0..20)
+        .map(|i| Trait {
+            display_type: None,
+            trait_type: format!("layer{}", i),
+            value: "<script>&".to_string(),
+        })
+        .collect::<Vec<_>>();
+    let svg = render_svg(1, &"P".repeat(100), &traits).unwrap();
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>"));
+    assert!(!svg.contains("<script>"));
+    assert!(svg.contains("layer0: &lt;script&gt;&amp;"));
+    assert!(svg.contains("layer11"));
+    assert!(!svg.contains("layer12"));
+    assert!(svg.contains(&format!(">{}<", "P".repeat(32))));
+    assert!(svg.len() <= MAX_SVG_BYTES);
+
+    // the largest id does not overflow the hue
+    let svg = render_svg(u128::MAX, "P", &[]).unwrap();
+    assert!(svg.contains("fill=\"hsl(15,60%,20%)\""));
+}
+
+#[test]
+pub fn render_svg_query() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+
+    for msg in [
+        ExecuteMsg::SetTraitLayers {
+            layers: trait_layers(),
+        },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(10u128).pow(6),
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp: RenderSvgResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::RenderSvg {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    let encoded = resp
+        .data_uri
+        .strip_prefix("data:image/svg+xml;base64,")
+        .unwrap();
+    let svg = String::from_utf8(Binary::from_base64(encoded).unwrap().to_vec()).unwrap();
+    assert!(svg.contains(">OPAN #1<"));
+    assert!(svg.contains("background: blue"));
+    assert!(svg.contains("eyes: "));
+}
+
+#[test]
+pub fn balance_snapshots_at_height() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let transfer_height = instantiate_resp.app.block_info().height;
+
+    instantiate_resp.app.update_block(|block| block.height += 1);
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(3u128) * units,
+            },
+            &[],
+        )
+        .unwrap();
+    instantiate_resp.app.update_block(|block| block.height += 1);
+
+    // balances are read at the beginning of the height
+    for (height, balance) in [
+        (transfer_height + 1, Uint128::zero()),
+        (transfer_height + 2, Uint128::from(3u128) * units),
+    ] {
+        let resp: BalanceResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                instantiate_resp.address.clone(),
+                &QueryMsg::BalanceAtHeight {
+                    address: "huy".to_string(),
+                    height,
+                },
+            )
+            .unwrap();
+        assert_eq!(resp, BalanceResponse { balance });
+    }
+
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::NftBalanceAtHeight {
+                address: "huy".to_string(),
+                height: transfer_height + 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp, NftBalanceResponse { count: 3 });
+
+    let resp: TotalSupplyAtHeightResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::TotalSupplyAtHeight {
+                height: transfer_height + 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        TotalSupplyAtHeightResponse {
+            total_supply: Uint128::from(10000u128) * units
+        }
+    );
+}
+
+#[test]
+pub fn nft_balance_follows_mint_and_burn() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    for msg in [
+        ExecuteMsg::SetCw721TransferExempt {
+            address: "pair".to_string(),
+            state: true,
+        },
+        ExecuteMsg::EnableTrading {
+            launch_height: None,
+            cooldown_blocks: 0,
+        },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(3u128) * units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp, NftBalanceResponse { count: 3 });
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "pair".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let resp: Erc721BalanceOfResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::Erc721BalanceOf {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        Erc721BalanceOfResponse {
+            count: 2,
+            expected: 2,
+            exempt: false,
+            in_sync: true,
+        }
+    );
+
+    let resp: Erc721BalanceOfResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::Erc721BalanceOf {
+                owner: "admin".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        Erc721BalanceOfResponse {
+            count: 0,
+            expected: 9997,
+            exempt: true,
+            in_sync: true,
+        }
+    );
+}
+
+#[test]
+pub fn check_invariants_and_reconcile() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    for msg in [
+        ExecuteMsg::EnableTrading {
+            launch_height: None,
+            cooldown_blocks: 0,
+        },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(3u128) * units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+    // transfers between non exempt accounts do not move NFTs yet, the holdings drift
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let check_invariants = |app: &App, address: &Addr| -> CheckInvariantsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                address.clone(),
+                &QueryMsg::CheckInvariants {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+    };
+    assert_eq!(
+        check_invariants(&instantiate_resp.app, &instantiate_resp.address),
+        CheckInvariantsResponse {
+            mismatches: vec![
+                AccountMismatch {
+                    address: "bob".to_string(),
+                    expected: 1,
+                    count: 0,
+                },
+                AccountMismatch {
+                    address: "huy".to_string(),
+                    expected: 2,
+                    count: 3,
+                },
+            ],
+            last_account: Some("huy".to_string()),
+            live_nfts: 3,
+            banked_nfts: 0,
+            nft_count: 3,
+            totals_in_sync: true,
+        }
+    );
+
+    // anyone can reconcile an account
+    for address in ["huy", "bob"] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("anyone"),
+                instantiate_resp.address.clone(),
+                &ExecuteMsg::Reconcile {
+                    address: address.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp = check_invariants(&instantiate_resp.app, &instantiate_resp.address);
+    assert_eq!(resp.mismatches, vec![]);
+    assert_eq!(resp.live_nfts, 3);
+    assert!(resp.totals_in_sync);
+}
+
+#[test]
+pub fn reconcile_does_not_mint_past_max_nft_supply() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(3u128));
+    let units = Uint128::from(10u128.pow(6));
+    let contract = instantiate_resp.address.clone();
+
+    for msg in [
+        ExecuteMsg::EnableTrading {
+            launch_height: None,
+            cooldown_blocks: 0,
+        },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(3u128) * units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(Addr::unchecked("admin"), contract.clone(), &msg, &[])
+            .unwrap();
+    }
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+
+    // every NFT of the max supply is held by huy, so bob can not get a new one minted
+    let reconcile = |address: &str| ExecuteMsg::Reconcile {
+        address: address.to_string(),
+    };
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            contract.clone(),
+            &reconcile("bob"),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MaxNftSupplyReached {}
+    );
+
+    // banking huy's surplus first hands it to bob
+    for address in ["huy", "bob"] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("anyone"),
+                contract.clone(),
+                &reconcile(address),
+                &[],
+            )
+            .unwrap();
+    }
+    let resp: CheckInvariantsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract,
+            &QueryMsg::CheckInvariants {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.mismatches, vec![]);
+    assert_eq!((resp.live_nfts, resp.nft_count), (3, 3));
+    assert!(resp.totals_in_sync);
+}
+
+#[test]
+pub fn holders_follow_balances() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    for msg in [
+        ExecuteMsg::EnableTrading {
+            launch_height: None,
+            cooldown_blocks: 0,
+        },
+        ExecuteMsg::Transfer {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(2u128) * units,
+        },
+        ExecuteMsg::Transfer {
+            recipient: "lan".to_string(),
+            amount: units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp: HolderCountResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::HolderCount {})
+        .unwrap();
+    assert_eq!(resp, HolderCountResponse { count: 3 });
+
+    let resp: AllAccountsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::AllAccounts {
+                start_after: Some("admin".to_string()),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.accounts, vec!["huy".to_string()]);
+
+    let resp: NftHoldersResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::NftHolders {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owners, vec!["huy".to_string(), "lan".to_string()]);
+
+    // lan sends everything away, dropping out of both holder sets
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("lan"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "admin".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let resp: HolderCountResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::HolderCount {})
+        .unwrap();
+    assert_eq!(resp, HolderCountResponse { count: 2 });
+
+    let resp: NftHoldersResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::NftHolders {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owners, vec!["huy".to_string()]);
+}
+
+#[test]
+pub fn bank_queries_follow_pop_order() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    let resp: NextFromBankResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::NextFromBank {})
+        .unwrap();
+    assert_eq!(
+        resp,
+        NextFromBankResponse {
+            token_id: "1".to_string(),
+            from_bank: false,
+        }
+    );
+
+    // huy gets nfts 1, 2 and 3, then sends 2 whole units back to the exempt admin
+    for (sender, msg) in [
+        (
+            "admin",
+            ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(3u128) * units,
+            },
+        ),
+        (
+            "huy",
+            ExecuteMsg::Transfer {
+                recipient: "admin".to_string(),
+                amount: Uint128::from(2u128) * units,
+            },
+        ),
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp: BankSizeResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::BankSize {})
+        .unwrap();
+    assert_eq!(resp, BankSizeResponse { size: 2 });
+
+    let resp: BankedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::BankedNfts {
+                start: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let next: NextFromBankResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(instantiate_resp.address.clone(), &QueryMsg::NextFromBank {})
+        .unwrap();
+    assert_eq!(next.token_id, resp.token_ids[0]);
+    assert!(next.from_bank);
+
+    let page: BankedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::BankedNfts {
+                start: Some(1),
+                limit: Some(5),
+            },
+        )
+        .unwrap();
+    assert_eq!(page.token_ids, resp.token_ids[1..].to_vec());
+
+    // The next nft handed out is the one at the head of the line
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "lan".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+    let resp: OwnerOfResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::OwnerOf {
+                token_id: next.token_id,
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owner, "lan".to_string());
+}
+
+#[test]
+pub fn reroll_swaps_for_a_different_nft() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    // huy gets nfts 1 and 2, lan gets nft 3 and banks it again
+    for (sender, msg) in [
+        (
+            "admin",
+            ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(2u128) * units + Uint128::from(500u128),
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "lan".to_string(),
+                amount: units,
+            },
+        ),
+        (
+            "lan",
+            ExecuteMsg::Transfer {
+                recipient: "admin".to_string(),
+                amount: units,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::SetRerollFee {
+                fee: Some(RerollFee::Fractions(Uint128::from(500u128))),
+            },
+        ),
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Reroll {
+                token_id: "1".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // 3 comes out of the bank and 1 goes in, the fee leaves the whole units untouched
+    let resp: OwnerOfResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::OwnerOf {
+                token_id: "3".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owner, "huy".to_string());
+    let resp: BankedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::BankedNfts {
+                start: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.token_ids, vec!["1".to_string()]);
+    let resp: BalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, Uint128::from(2u128) * units);
+
+    // No fractions left to pay the fee without losing a whole unit
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Reroll {
+                token_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientFractions {}
+    );
+
+    // Native fee, the bank only holds 1 so the next reroll mints a new id
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("huy"), coins(150, "orai"))
+        })
+        .unwrap();
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetRerollFee {
+                fee: Some(RerollFee::Native(coin(100, "orai"))),
+            },
+            &[],
+        )
+        .unwrap();
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Reroll {
+                token_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Payment(PaymentError::NoFunds {})
+    );
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Reroll {
+                token_id: "2".to_string(),
+            },
+            &coins(150, "orai"),
+        )
+        .unwrap();
+    let resp: OwnerOfResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::OwnerOf {
+                token_id: "1".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owner, "huy".to_string());
+    let treasury_balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("admin", "orai")
+        .unwrap();
+    assert_eq!(treasury_balance.amount, Uint128::from(100u128));
+    // the overpayment is refunded
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("huy", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(50u128));
+}
+
+#[test]
+pub fn locked_nfts_are_not_banked() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    // huy gets nfts 1 and 2 and locks 2, the one a fractional transfer would bank first
+    for (sender, msg) in [
+        (
+            "admin",
+            ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(2u128) * units,
+            },
+        ),
+        (
+            "huy",
+            ExecuteMsg::LockNft {
+                token_id: "2".to_string(),
+            },
+        ),
+        (
+            "huy",
+            ExecuteMsg::Transfer {
+                recipient: "admin".to_string(),
+                amount: units,
+            },
+        ),
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    let resp: BankedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::BankedNfts {
+                start: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.token_ids, vec!["1".to_string()]);
+    let resp: LockedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::LockedNfts {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.token_ids, vec!["2".to_string()]);
+
+    // Only the locked nft is left, the transfer fails until it is unlocked
+    let transfer = ExecuteMsg::Transfer {
+        recipient: "admin".to_string(),
+        amount: units,
+    };
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &transfer,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OnlyLockedNfts {
+            address: "huy".to_string()
+        }
+    );
+
+    for msg in [
+        ExecuteMsg::UnlockNft {
+            token_id: "2".to_string(),
+        },
+        transfer,
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("huy"),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+    let resp: LockedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::LockedNfts {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert!(resp.token_ids.is_empty());
+}
+
+#[test]
+pub fn nft_order_sets_withdrawal_order() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+
+    for (sender, msg) in [
+        (
+            "admin",
+            ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(3u128) * units,
+            },
+        ),
+        (
+            "huy",
+            ExecuteMsg::SetNftOrder {
+                token_ids: vec!["1".to_string(), "3".to_string()],
+            },
+        ),
+        (
+            "huy",
+            ExecuteMsg::Transfer {
+                recipient: "admin".to_string(),
+                amount: units,
+            },
+        ),
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                instantiate_resp.address.clone(),
+                &msg,
+                &[],
+            )
+            .unwrap();
+    }
+
+    // 1 left first and is dropped from the order
+    let resp: BankedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::BankedNfts {
+                start: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.token_ids, vec!["1".to_string()]);
+    let resp: NftOrderResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address.clone(),
+            &QueryMsg::NftOrder {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.token_ids, vec!["3".to_string()]);
+
+    // 1 is banked, no one owns it
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetNftOrder {
+                token_ids: vec!["1".to_string()],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Std(StdError::NotFound { .. })
+    ));
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetNftOrder {
+                token_ids: vec!["2".to_string(), "2".to_string()],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DuplicateTokenId {
+            token_id: "2".to_string()
+        }
+    );
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("lan"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::SetNftOrder {
+                token_ids: vec!["2".to_string()],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            instantiate_resp.address.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "admin".to_string(),
+                amount: units,
+            },
+            &[],
+        )
+        .unwrap();
+    let resp: OwnerOfResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            instantiate_resp.address,
+            &QueryMsg::OwnerOf {
+                token_id: "2".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owner, "huy".to_string());
+}
+
+#[test]
+pub fn staked_nfts_earn_and_stay_in_custody() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let contract = instantiate_resp.address.clone();
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &contract, coins(1000, "orai"))
+        })
+        .unwrap();
+
+    for (sender, msg) in [
+        (
+            "admin",
+            ExecuteMsg::SetNftStakingConfig {
+                reward_denom: Denom::Native("orai".to_string()),
+                reward_per_block: Uint128::from(10u128),
+                unbonding_period: Duration::Height(5),
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(2u128) * units,
+            },
+        ),
+        (
+            "huy",
+            ExecuteMsg::StakeNft {
+                token_id: "2".to_string(),
+            },
+        ),
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(Addr::unchecked(sender), contract.clone(), &msg, &[])
+            .unwrap();
+    }
+    instantiate_resp
+        .app
+        .update_block(|block| block.height += 10);
+
+    // The unstaked nft leaves first, the units backing the staked one can not move
+    let transfer = ExecuteMsg::Transfer {
+        recipient: "admin".to_string(),
+        amount: units,
+    };
+    instantiate_resp
+        .app
+        .execute_contract(Addr::unchecked("huy"), contract.clone(), &transfer, &[])
+        .unwrap();
+    let resp: BankedNftsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::BankedNfts {
+                start: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.token_ids, vec!["1".to_string()]);
+    let err = instantiate_resp
+        .app
+        .execute_contract(Addr::unchecked("huy"), contract.clone(), &transfer, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::StakedBalance { staked: 1 }
+    );
+
+    let resp: PendingNftRewardsResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::PendingNftRewards {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.rewards, Uint128::from(100u128));
+    for msg in [
+        ExecuteMsg::ClaimRewards {},
+        ExecuteMsg::UnstakeNft {
+            token_id: "2".to_string(),
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(Addr::unchecked("huy"), contract.clone(), &msg, &[])
+            .unwrap();
+    }
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("huy", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(100u128));
+
+    // Unbonding nfts stop earning but stay in custody until the period ends
+    instantiate_resp.app.update_block(|block| block.height += 3);
+    let resp: NftStakesResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftStakes {
+                owner: "huy".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.stakes.len(), 1);
+    assert_eq!(resp.stakes[0].pending_rewards, Uint128::zero());
+    instantiate_resp
+        .app
+        .execute_contract(Addr::unchecked("huy"), contract.clone(), &transfer, &[])
+        .unwrap_err();
+
+    instantiate_resp.app.update_block(|block| block.height += 2);
+    instantiate_resp
+        .app
+        .execute_contract(Addr::unchecked("huy"), contract.clone(), &transfer, &[])
+        .unwrap();
+    let resp: NftStakesResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract,
+            &QueryMsg::NftStakes {
+                owner: "huy".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(resp.stakes.is_empty());
+}
+
+#[test]
+pub fn staked_fractions_share_rewards() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let half = Uint128::from(500000u128);
+    let contract = instantiate_resp.address.clone();
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &contract, coins(10000, "orai"))
+        })
+        .unwrap();
+
+    for (sender, msg) in [
+        (
+            "admin",
+            ExecuteMsg::SetFractionStakingConfig {
+                reward_denom: Denom::Native("orai".to_string()),
+                reward_per_block: Uint128::from(100u128),
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "huy".to_string(),
+                amount: Uint128::from(2u128) * units,
+            },
+        ),
+        (
+            "admin",
+            ExecuteMsg::Transfer {
+                recipient: "lan".to_string(),
+                amount: units,
+            },
+        ),
+        (
+            "huy",
+            ExecuteMsg::StakeFractions {
+                amount: units + half,
+            },
+        ),
+        ("lan", ExecuteMsg::StakeFractions { amount: half }),
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(Addr::unchecked(sender), contract.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    // Both of huy's nfts and lan's only nft are banked with the whole units they staked
+    for owner in ["huy", "lan"] {
+        let resp: NftBalanceResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                contract.clone(),
+                &QueryMsg::NftBalance {
+                    owner: owner.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.count, 0);
+    }
+
+    instantiate_resp
+        .app
+        .update_block(|block| block.height += 10);
+    let resp: FractionStakeResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::FractionStake {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        FractionStakeResponse {
+            amount: units + half,
+            pending_rewards: Uint128::from(750u128),
+        }
+    );
+
+    for msg in [
+        ExecuteMsg::UnstakeFractions { amount: units },
+        ExecuteMsg::ClaimFractionRewards {},
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(Addr::unchecked("huy"), contract.clone(), &msg, &[])
+            .unwrap();
+    }
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 1);
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("huy", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(750u128));
+
+    let resp: FractionStakingConfigResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract.clone(), &QueryMsg::FractionStakingConfig {})
+        .unwrap();
+    assert_eq!(resp.total_staked, units);
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("lan"),
+            contract,
+            &ExecuteMsg::UnstakeFractions { amount: units },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientStake { staked: half }
+    );
+}
+
+#[test]
+pub fn native_sale_mints_within_caps() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let contract = instantiate_resp.address.clone();
+    let now = instantiate_resp.app.block_info().time;
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            for buyer in ["huy", "lan"] {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(buyer), coins(1000, "orai"))?;
+            }
+            Ok::<_, anyhow::Error>(())
+        })
+        .unwrap();
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::SetSaleConfig {
+                denom: "orai".to_string(),
+                pricing: Pricing::Fixed {
+                    price_per_unit: Uint128::from(100u128),
+                },
+                start_time: now,
+                end_time: now.plus_seconds(1000),
+                per_wallet_cap: Uint128::from(2u128) * units,
+                total_cap: Uint128::from(3u128) * units,
+            },
+            &[],
+        )
+        .unwrap();
+
+    // huy pays for 2.5 units, gets the 2 of the wallet cap and a refund of the rest
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::BuyWithNative {
+                amount: Some(Uint128::from(2500000u128)),
+            },
+            &coins(250, "orai"),
+        )
+        .unwrap();
+    let resp: BalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, Uint128::from(2u128) * units);
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 2);
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("huy", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(800u128));
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::BuyWithNative {
+                amount: Some(units),
+            },
+            &coins(100, "orai"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SaleCapReached {}
+    );
+
+    // lan takes the last unit of the total cap
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("lan"),
+            contract.clone(),
+            &ExecuteMsg::BuyWithNative {
+                amount: Some(units),
+            },
+            &coins(150, "orai"),
+        )
+        .unwrap();
+    let resp: SaleInfoResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract.clone(), &QueryMsg::SaleInfo {})
+        .unwrap();
+    assert_eq!(resp.sold, Uint128::from(3u128) * units);
+    assert_eq!(resp.proceeds, Uint128::from(300u128));
+    assert!(!resp.active);
+    let resp: TokenInfoResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract.clone(), &QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(resp.total_supply, Uint128::from(10003u128) * units);
+
+    // rewards can not be paid out of the proceeds
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::SetFractionStakingConfig {
+                reward_denom: Denom::Native("orai".to_string()),
+                reward_per_block: Uint128::from(10u128),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRewardDenom {}
+    );
+
+    // the denom can not change while orai proceeds are waiting to be withdrawn
+    let set_usdc_sale = ExecuteMsg::SetSaleConfig {
+        denom: "usdc".to_string(),
+        pricing: Pricing::Fixed {
+            price_per_unit: Uint128::from(100u128),
+        },
+        start_time: now,
+        end_time: now.plus_seconds(1000),
+        per_wallet_cap: Uint128::from(2u128) * units,
+        total_cap: Uint128::from(3u128) * units,
+    };
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &set_usdc_sale,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidSaleConfig {}
+    );
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::WithdrawProceeds {
+                recipient: Some("treasury".to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("treasury", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(300u128));
+
+    // once withdrawn, the denom can change
+    instantiate_resp
+        .app
+        .execute_contract(Addr::unchecked("admin"), contract, &set_usdc_sale, &[])
+        .unwrap();
+}
+
+#[test]
+pub fn whitelist_phases_verify_merkle_proofs() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let contract = instantiate_resp.address.clone();
+    let now = instantiate_resp.app.block_info().time;
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("huy"), coins(1000, "orai"))
+        })
+        .unwrap();
+
+    // Two leaves, each one is the proof of the other
+    let huy_allocation = Uint128::from(2u128) * units;
+    let huy_leaf = merkle_leaf(&Addr::unchecked("huy"), huy_allocation);
+    let lan_leaf = merkle_leaf(&Addr::unchecked("lan"), units);
+    assert_eq!(
+        lan_leaf.to_vec(),
+        Sha256::digest(format!("lan:{}", units).as_bytes()).to_vec()
+    );
+    let mut leaves = [huy_leaf, lan_leaf];
+    leaves.sort();
+    let merkle_root = Binary::from(Sha256::digest(leaves.concat()).to_vec());
+    let huy_proof = vec![Binary::from(lan_leaf.to_vec())];
+
+    for (phase_id, price_per_unit) in [(1, None), (2, Some(coin(100, "orai")))] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("admin"),
+                contract.clone(),
+                &ExecuteMsg::SetWhitelistPhase {
+                    phase_id,
+                    merkle_root: merkle_root.clone(),
+                    start_time: now,
+                    end_time: now.plus_seconds(1000),
+                    price_per_unit,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    let claim = ExecuteMsg::ClaimAirdrop {
+        amount: huy_allocation,
+        proof: huy_proof.clone(),
+    };
+    instantiate_resp
+        .app
+        .execute_contract(Addr::unchecked("huy"), contract.clone(), &claim, &[])
+        .unwrap();
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 2);
+    let err = instantiate_resp
+        .app
+        .execute_contract(Addr::unchecked("huy"), contract.clone(), &claim, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AllocationClaimed {}
+    );
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("lan"),
+            contract.clone(),
+            &ExecuteMsg::ClaimAirdrop {
+                amount: huy_allocation,
+                proof: vec![Binary::from(huy_leaf.to_vec())],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMerkleProof {}
+    );
+
+    // The priced phase sells up to the allocation and refunds the rest
+    for funds in [150, 100] {
+        instantiate_resp
+            .app
+            .execute_contract(
+                Addr::unchecked("huy"),
+                contract.clone(),
+                &ExecuteMsg::WhitelistBuy {
+                    amount: huy_allocation,
+                    proof: huy_proof.clone(),
+                },
+                &coins(funds, "orai"),
+            )
+            .unwrap();
+    }
+    let resp: WhitelistClaimedResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::WhitelistClaimed {
+                phase_id: 2,
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.claimed, huy_allocation);
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("huy", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(800u128));
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract,
+            &ExecuteMsg::WithdrawProceeds { recipient: None },
+            &[],
+        )
+        .unwrap();
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("admin", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(200u128));
+}
+
+#[test]
+pub fn dutch_auction_and_bonding_curve_pricing() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let contract = instantiate_resp.address.clone();
+    let block = instantiate_resp.app.block_info();
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("huy"), coins(1000, "orai"))
+        })
+        .unwrap();
+    let set_sale_config = |pricing| ExecuteMsg::SetSaleConfig {
+        denom: "orai".to_string(),
+        pricing,
+        start_time: block.time,
+        end_time: block.time.plus_seconds(10000),
+        per_wallet_cap: Uint128::from(10u128) * units,
+        total_cap: Uint128::from(10u128) * units,
+    };
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &set_sale_config(Pricing::DutchAuction {
+                start_price: Uint128::from(1000u128),
+                floor_price: Uint128::from(100u128),
+                start_height: block.height,
+                decay_blocks: 10,
+            }),
+            &[],
+        )
+        .unwrap();
+    for (blocks, expected) in [(0, 1000u128), (5, 550), (15, 100)] {
+        instantiate_resp
+            .app
+            .update_block(|b| b.height = block.height + blocks);
+        let resp: CurrentPriceResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(contract.clone(), &QueryMsg::CurrentPrice { amount: units })
+            .unwrap();
+        assert_eq!(resp.price, Uint128::from(expected));
+    }
+
+    // Each whole unit costs one increment more, the half unit is priced as the third unit.
+    // NFTs minted outside of the sale do not move the curve.
+    for msg in [
+        set_sale_config(Pricing::BondingCurve {
+            base_price: Uint128::from(100u128),
+            increment: Uint128::from(10u128),
+        }),
+        ExecuteMsg::Transfer {
+            recipient: "lan".to_string(),
+            amount: units,
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(Addr::unchecked("admin"), contract.clone(), &msg, &[])
+            .unwrap();
+    }
+    let resp: CurrentPriceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::CurrentPrice {
+                amount: Uint128::from(2500000u128),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.price, Uint128::from(270u128));
+
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::BuyWithNative {
+                amount: Some(Uint128::from(2u128) * units),
+            },
+            &coins(200, "orai"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientPayment {}
+    );
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::BuyWithNative {
+                amount: Some(Uint128::from(2u128) * units),
+            },
+            &coins(300, "orai"),
+        )
+        .unwrap();
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("huy", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, Uint128::from(790u128));
+
+    // Two NFTs were minted, the next unit costs more
+    let resp: CurrentPriceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract.clone(), &QueryMsg::CurrentPrice { amount: units })
+        .unwrap();
+    assert_eq!(resp.price, Uint128::from(120u128));
+
+    // Without an amount the payment buys as much as it covers, 120 + 130 for two more units
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::BuyWithNative { amount: None },
+            &coins(250, "orai"),
+        )
+        .unwrap();
+    let resp: BalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract,
+            &QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, Uint128::from(4u128) * units);
+
+    // A fraction already sold splits the next unit between two prices
+    let pricing = Pricing::BondingCurve {
+        base_price: Uint128::from(100u128),
+        increment: Uint128::from(10u128),
+    };
+    let cost = sale_cost(&pricing, 0, Uint128::from(1500000u128), units, units).unwrap();
+    assert_eq!(cost, Uint128::from(115u128));
+}
+
+#[test]
+pub fn vesting_schedules_release_after_cliff() {
+    let units = Uint128::from(10u128.pow(6));
+    let mut instantiate_resp = intantisate_contract_with_msg(InstantiateMsg {
+        vesting_schedules: Some(vec![VestingScheduleMsg {
+            beneficiary: "huy".to_string(),
+            amount: Uint128::from(10u128),
+            cliff: Duration::Height(10),
+            duration: Duration::Height(100),
+        }]),
+        ..default_instantiate_msg(Uint128::from(10u128))
+    });
+    let contract = instantiate_resp.address.clone();
+
+    let resp: TokenInfoResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract.clone(), &QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(resp.total_supply, Uint128::from(20u128) * units);
+
+    // nothing vests before the cliff
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::ClaimVested {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NothingToClaim {}
+    );
+
+    instantiate_resp
+        .app
+        .update_block(|block| block.height += 25);
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::ClaimVested {},
+            &[],
+        )
+        .unwrap();
+    let resp: VestingResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::Vesting {
+                beneficiary: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        VestingResponse {
+            total: Uint128::from(10u128) * units,
+            vested: Uint128::from(2500000u128),
+            claimed: Uint128::from(2500000u128),
+            locked: Uint128::from(7500000u128),
+        }
+    );
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 2);
+
+    // the admin locks part of its balance for lan, mixing heights and time is rejected
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::CreateVestingSchedule {
+                schedule: VestingScheduleMsg {
+                    beneficiary: "lan".to_string(),
+                    amount: Uint128::from(3u128) * units,
+                    cliff: Duration::Height(10),
+                    duration: Duration::Time(1000),
+                },
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidVestingSchedule {}
+    );
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::CreateVestingSchedule {
+                schedule: VestingScheduleMsg {
+                    beneficiary: "lan".to_string(),
+                    amount: Uint128::from(3u128) * units,
+                    cliff: Duration::Time(100),
+                    duration: Duration::Time(1000),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    // a beneficiary has one schedule at a time
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::CreateVestingSchedule {
+                schedule: VestingScheduleMsg {
+                    beneficiary: "lan".to_string(),
+                    amount: units,
+                    cliff: Duration::Time(100),
+                    duration: Duration::Time(1000),
+                },
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::VestingScheduleExists {
+            beneficiary: "lan".to_string()
+        }
+    );
+
+    instantiate_resp
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(1000));
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("lan"),
+            contract.clone(),
+            &ExecuteMsg::ClaimVested {},
+            &[],
+        )
+        .unwrap();
+    let resp: BalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::Balance {
+                address: "lan".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, Uint128::from(3u128) * units);
+
+    // a fully claimed schedule is removed and reports nothing left
+    let resp: VestingResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::Vesting {
+                beneficiary: "lan".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.total, Uint128::zero());
+    assert_eq!(resp.locked, Uint128::zero());
+
+    // a non exempt admin gets the NFTs of the units it locks banked
+    for msg in [
+        ExecuteMsg::SetCw721TransferExempt {
+            address: "admin".to_string(),
+            state: false,
+        },
+        ExecuteMsg::Reconcile {
+            address: "admin".to_string(),
+        },
+        ExecuteMsg::CreateVestingSchedule {
+            schedule: VestingScheduleMsg {
+                beneficiary: "tung".to_string(),
+                amount: Uint128::from(2u128) * units,
+                cliff: Duration::Height(0),
+                duration: Duration::Height(10),
+            },
+        },
+    ] {
+        instantiate_resp
+            .app
+            .execute_contract(Addr::unchecked("admin"), contract.clone(), &msg, &[])
+            .unwrap();
+    }
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract,
+            &QueryMsg::NftBalance {
+                owner: "admin".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 5);
+}
+
+#[test]
+pub fn batch_transfers_settle_nfts_once() {
+    let mut instantiate_resp: InstantiateResponse = intantisate_contract(Uint128::from(10000u128));
+    let units = Uint128::from(10u128.pow(6));
+    let contract = instantiate_resp.address.clone();
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    ("huy".to_string(), Uint128::from(2500000u128)),
+                    ("lan".to_string(), units),
+                    ("huy".to_string(), Uint128::from(500000u128)),
+                ],
+            },
+            &[],
+        )
+        .unwrap();
+    for (owner, count) in [("huy", 3), ("lan", 1)] {
+        let resp: NftBalanceResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                contract.clone(),
+                &QueryMsg::NftBalance {
+                    owner: owner.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.count, count);
+    }
+
+    // the failing item is reported and nothing of the batch is applied
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    ("huy".to_string(), units),
+                    ("lan".to_string(), Uint128::from(10000u128) * units),
+                ],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BatchItemFailed { index: 1, .. }
+    ));
+    let resp: BalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, Uint128::from(3u128) * units);
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::EnableTrading {
+                launch_height: None,
+                cooldown_blocks: 0,
+            },
+            &[],
+        )
+        .unwrap();
+
+    // huy hands token 2 itself to lan, token 4 is lan's
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::BatchTransferNft {
+                transfers: vec![
+                    ("lan".to_string(), "2".to_string()),
+                    ("lan".to_string(), "4".to_string()),
+                ],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BatchItemFailed {
+            index: 1,
+            error: ContractError::Unauthorized {}.to_string(),
+        }
+    );
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::BatchTransferNft {
+                transfers: vec![("lan".to_string(), "2".to_string())],
+            },
+            &[],
+        )
+        .unwrap();
+    let resp: OwnerOfResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::OwnerOf {
+                token_id: "2".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owner, "lan".to_string());
+    for (owner, count) in [("huy", 2), ("lan", 2)] {
+        let resp: NftBalanceResponse = instantiate_resp
+            .app
+            .wrap()
+            .query_wasm_smart(
+                contract.clone(),
+                &QueryMsg::NftBalance {
+                    owner: owner.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(resp.count, count);
+    }
+    let resp: BankSizeResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract, &QueryMsg::BankSize {})
+        .unwrap();
+    assert_eq!(resp.size, 0);
+}
+
+#[test]
+pub fn wrapper_deposits_and_withdraws_native() {
+    let units = Uint128::from(10u128.pow(6));
+    let mut instantiate_resp = intantisate_contract_with_msg(InstantiateMsg {
+        initial_balances: vec![],
+        underlying: Some(Denom::Native("orai".to_string())),
+        ..default_instantiate_msg(Uint128::zero())
+    });
+    let contract = instantiate_resp.address.clone();
+    instantiate_resp
+        .app
+        .init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("huy"), coins(2500000, "orai"))
+        })
+        .unwrap();
+
+    // a wrapper can not mint unbacked supply
+    let now = instantiate_resp.app.block_info().time;
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::SetSaleConfig {
+                denom: "orai".to_string(),
+                pricing: Pricing::Fixed {
+                    price_per_unit: Uint128::from(100u128),
+                },
+                start_time: now,
+                end_time: now.plus_seconds(1000),
+                per_wallet_cap: units,
+                total_cap: units,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnbackedSupply {}
+    );
+
+    // nor pay rewards out of the deposits backing its supply
+    let err = instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::SetNftStakingConfig {
+                reward_denom: Denom::Native("orai".to_string()),
+                reward_per_block: Uint128::from(10u128),
+                unbonding_period: Duration::Height(5),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRewardDenom {}
+    );
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::Deposit {},
+            &coins(2500000, "orai"),
+        )
+        .unwrap();
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 2);
+
+    instantiate_resp
+        .app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::Withdraw { amount: units },
+            &[],
+        )
+        .unwrap();
+    let balance = instantiate_resp
+        .app
+        .wrap()
+        .query_balance("huy", "orai")
+        .unwrap();
+    assert_eq!(balance.amount, units);
+    let resp: NftBalanceResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 1);
+    let resp: BankSizeResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract.clone(), &QueryMsg::BankSize {})
+        .unwrap();
+    assert_eq!(resp.size, 1);
+    let resp: TokenInfoResponse = instantiate_resp
+        .app
+        .wrap()
+        .query_wasm_smart(contract, &QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(resp.total_supply, Uint128::from(1500000u128));
+    assert_eq!(resp.underlying, Some(Denom::Native("orai".to_string())));
+}
+
+// Minimal external cw20, only what wrapping needs
+#[cw_serde]
+enum MockCw20ExecuteMsg {
+    Mint {
+        recipient: String,
+        amount: Uint128,
+    },
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+}
+
+const MOCK_CW20_BALANCES: Map<&str, Uint128> = Map::new("mock_balance");
+
+fn mock_cw20_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_cw20_move(deps: DepsMut, from: &str, to: &str, amount: Uint128) -> StdResult<()> {
+    let balance = MOCK_CW20_BALANCES
+        .may_load(deps.storage, from)?
+        .unwrap_or_default();
+    MOCK_CW20_BALANCES.save(deps.storage, from, &balance.checked_sub(amount)?)?;
+    MOCK_CW20_BALANCES.update(deps.storage, to, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+fn mock_cw20_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: MockCw20ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        MockCw20ExecuteMsg::Mint { recipient, amount } => {
+            MOCK_CW20_BALANCES.save(deps.storage, &recipient, &amount)?;
+            Ok(Response::new())
+        }
+        MockCw20ExecuteMsg::Transfer { recipient, amount } => {
+            mock_cw20_move(deps, info.sender.as_str(), &recipient, amount)?;
+            Ok(Response::new())
+        }
+        MockCw20ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => {
+            mock_cw20_move(deps, info.sender.as_str(), &contract, amount)?;
+            let receive_msg = Cw20ReceiveMsg {
+                sender: info.sender.to_string(),
+                amount,
+                msg,
+            };
+            Ok(Response::new().add_message(receive_msg.into_cosmos_msg(contract)?))
+        }
+    }
+}
+
+fn mock_cw20_query(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+    match msg {
+        Cw20QueryMsg::Balance { address } => to_json_binary(&BalanceResponse {
+            balance: MOCK_CW20_BALANCES
+                .may_load(deps.storage, &address)?
+                .unwrap_or_default(),
+        }),
+        _ => Err(StdError::generic_err("unsupported")),
+    }
+}
+
+#[test]
+pub fn wrapper_deposits_and_withdraws_cw20() {
+    let mut app = App::default();
+    let units = Uint128::from(10u128.pow(6));
+    let mock_code = ContractWrapper::new(mock_cw20_execute, mock_cw20_instantiate, mock_cw20_query);
+    let mock_code_id = app.store_code(Box::new(mock_code));
+    let underlying = app
+        .instantiate_contract(
+            mock_code_id,
+            Addr::unchecked("admin"),
+            &Empty {},
+            &[],
+            "underlying",
+            None,
+        )
+        .unwrap();
+    let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+    let contract = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked("admin"),
+            &InstantiateMsg {
+                initial_balances: vec![],
+                underlying: Some(Denom::Cw20(underlying.clone())),
+                ..default_instantiate_msg(Uint128::zero())
+            },
+            &[],
+            "cw404 contract",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked("admin"),
+        underlying.clone(),
+        &MockCw20ExecuteMsg::Mint {
+            recipient: "huy".to_string(),
+            amount: Uint128::from(2500000u128),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // rewards can not be paid out of the deposits
+    let err = app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::SetFractionStakingConfig {
+                reward_denom: Denom::Cw20(underlying.clone()),
+                reward_per_block: Uint128::from(10u128),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRewardDenom {}
+    );
+
+    // only the underlying token can deposit
+    let err = app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "huy".to_string(),
+                amount: units,
+                msg: Binary::default(),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidUnderlying {}
+    );
+
+    app.execute_contract(
+        Addr::unchecked("huy"),
+        underlying.clone(),
+        &MockCw20ExecuteMsg::Send {
+            contract: contract.to_string(),
+            amount: Uint128::from(2500000u128),
+            msg: Binary::default(),
+        },
+        &[],
+    )
+    .unwrap();
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, Uint128::from(2500000u128));
+    let resp: NftBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 2);
+
+    // withdraw does not take funds
+    app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &Addr::unchecked("huy"), coins(100, "orai"))
+    })
+    .unwrap();
+    let err = app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::Withdraw { amount: units },
+            &coins(100, "orai"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Payment(PaymentError::NonPayable {})
+    );
+
+    app.execute_contract(
+        Addr::unchecked("huy"),
+        contract.clone(),
+        &ExecuteMsg::Withdraw { amount: units },
+        &[],
+    )
+    .unwrap();
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            underlying.clone(),
+            &Cw20QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, units);
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            underlying,
+            &Cw20QueryMsg::Balance {
+                address: contract.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, Uint128::from(1500000u128));
+    let resp: NftBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract,
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 1);
+}
+
+// Minimal external collection, only what wrapping needs
+#[cw_serde]
+enum MockCw721ExecuteMsg {
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: String,
+        extension: Option<MockMetadata>,
+    },
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+}
+
+// A richer metadata schema than Metadata
+#[cw_serde]
+struct MockMetadata {
+    name: Option<String>,
+    image: Option<String>,
+    external_url: Option<String>,
+}
+
+// (owner, token_uri, extension) keyed by token_id
+const MOCK_CW721_TOKENS: Map<&str, (String, String, Option<MockMetadata>)> = Map::new("mock_token");
+
+fn mock_cw721_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_cw721_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: MockCw721ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        MockCw721ExecuteMsg::Mint {
+            token_id,
+            owner,
+            token_uri,
+            extension,
+        } => {
+            MOCK_CW721_TOKENS.save(deps.storage, &token_id, &(owner, token_uri, extension))?;
+            Ok(Response::new())
+        }
+        MockCw721ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => {
+            let (owner, token_uri, extension) = MOCK_CW721_TOKENS.load(deps.storage, &token_id)?;
+            if owner != info.sender.as_str() {
+                return Err(StdError::generic_err("not owner"));
+            }
+            MOCK_CW721_TOKENS.save(deps.storage, &token_id, &(recipient, token_uri, extension))?;
+            Ok(Response::new())
+        }
+        MockCw721ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => {
+            let (owner, token_uri, extension) = MOCK_CW721_TOKENS.load(deps.storage, &token_id)?;
+            if owner != info.sender.as_str() {
+                return Err(StdError::generic_err("not owner"));
+            }
+            MOCK_CW721_TOKENS.save(
+                deps.storage,
+                &token_id,
+                &(contract.clone(), token_uri, extension),
+            )?;
+            let receive_msg = Cw721ReceiveMsg {
+                sender: info.sender.to_string(),
+                token_id,
+                msg,
+            };
+            Ok(Response::new().add_message(receive_msg.into_cosmos_msg(contract)?))
+        }
+    }
+}
+
+fn mock_cw721_query(deps: Deps, _env: Env, msg: Cw721QueryMsg) -> StdResult<Binary> {
+    match msg {
+        Cw721QueryMsg::NftInfo { token_id } => {
+            let (_, token_uri, extension) = MOCK_CW721_TOKENS.load(deps.storage, &token_id)?;
+            to_json_binary(&NftInfoResponse {
+                token_uri: Some(token_uri),
+                extension,
+            })
+        }
+        Cw721QueryMsg::OwnerOf { token_id, .. } => {
+            let (owner, ..) = MOCK_CW721_TOKENS.load(deps.storage, &token_id)?;
+            to_json_binary(&OwnerOfResponse {
+                owner,
+                approvals: vec![],
+            })
+        }
+        _ => Err(StdError::generic_err("unsupported")),
+    }
+}
+
+#[test]
+pub fn wrapped_collection_mirrors_original_ids() {
+    let mut app = App::default();
+    let units = Uint128::from(10u128.pow(6));
+    let mock_code =
+        ContractWrapper::new(mock_cw721_execute, mock_cw721_instantiate, mock_cw721_query);
+    let mock_code_id = app.store_code(Box::new(mock_code));
+    let collection = app
+        .instantiate_contract(
+            mock_code_id,
+            Addr::unchecked("admin"),
+            &Empty {},
+            &[],
+            "collection",
+            None,
+        )
+        .unwrap();
+    let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+    let contract = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked("admin"),
+            &InstantiateMsg {
+                initial_balances: vec![],
+                wrapped_collection: Some(collection.to_string()),
+                ..default_instantiate_msg(Uint128::zero())
+            },
+            &[],
+            "cw404 contract",
+            None,
+        )
+        .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked("admin"),
+        collection.clone(),
+        &MockCw721ExecuteMsg::Mint {
+            token_id: "genesis-7".to_string(),
+            owner: "huy".to_string(),
+            token_uri: "ipfs://original/genesis-7".to_string(),
+            extension: Some(MockMetadata {
+                name: Some("Genesis #7".to_string()),
+                image: Some("ipfs://original/genesis-7.png".to_string()),
+                external_url: Some("https://original.xyz/7".to_string()),
+            }),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // only the wrapped collection can deposit
+    let err = app
+        .execute_contract(
+            Addr::unchecked("huy"),
+            contract.clone(),
+            &ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: "huy".to_string(),
+                token_id: "genesis-7".to_string(),
+                msg: Binary::default(),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidUnderlying {}
+    );
+
+    app.execute_contract(
+        Addr::unchecked("huy"),
+        collection.clone(),
+        &MockCw721ExecuteMsg::SendNft {
+            contract: contract.to_string(),
+            token_id: "genesis-7".to_string(),
+            msg: Binary::default(),
+        },
+        &[],
+    )
+    .unwrap();
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::Balance {
+                address: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.balance, units);
+    let resp: NftInfoResponse<Extension> = app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftInfo {
+                token_id: "genesis-7".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.token_uri,
+        Some("ipfs://original/genesis-7".to_string())
+    );
+    let original_extension = Some(Metadata {
+        name: Some("Genesis #7".to_string()),
+        image: Some("ipfs://original/genesis-7.png".to_string()),
+        ..Metadata::default()
+    });
+    assert_eq!(resp.extension, original_extension);
+    let resp: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::OwnerOf {
+                token_id: "genesis-7".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owner, "huy".to_string());
+
+    // the deposited NFT is counted, and no other id can be handed out
+    let resp: CheckInvariantsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::CheckInvariants {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!((resp.live_nfts, resp.nft_count), (1, 1));
+    assert!(resp.totals_in_sync);
+    app.wrap()
+        .query_wasm_smart::<NextFromBankResponse>(contract.clone(), &QueryMsg::NextFromBank {})
+        .unwrap_err();
+
+    // overrides only apply to deposited ids
+    app.execute_contract(
+        Addr::unchecked("admin"),
+        contract.clone(),
+        &ExecuteMsg::SetTokenRoyaltyInfo {
+            token_id: "genesis-7".to_string(),
+            royalty: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            Addr::unchecked("admin"),
+            contract.clone(),
+            &ExecuteMsg::SetTokenRoyaltyInfo {
+                token_id: "1".to_string(),
+                royalty: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTokenId {
+            token_id: "1".to_string()
+        }
+    );
+
+    app.execute_contract(
+        Addr::unchecked("admin"),
+        contract.clone(),
+        &ExecuteMsg::FreezeMetadata {},
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked("huy"),
+        contract.clone(),
+        &ExecuteMsg::RedeemNft {
+            token_id: "genesis-7".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    let resp: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            collection.clone(),
+            &Cw721QueryMsg::OwnerOf {
+                token_id: "genesis-7".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.owner, "huy".to_string());
+    let resp: TokenInfoResponse = app
+        .wrap()
+        .query_wasm_smart(contract.clone(), &QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(resp.total_supply, Uint128::zero());
+    let resp: NftBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract.clone(),
+            &QueryMsg::NftBalance {
+                owner: "huy".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.count, 0);
+
+    // frozen metadata is kept when the original changes and the token is wrapped again
+    app.execute_contract(
+        Addr::unchecked("admin"),
+        collection.clone(),
+        &MockCw721ExecuteMsg::Mint {
+            token_id: "genesis-7".to_string(),
+            owner: "huy".to_string(),
+            token_uri: "ipfs://changed/genesis-7".to_string(),
+            extension: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked("huy"),
+        collection,
+        &MockCw721ExecuteMsg::SendNft {
+            contract: contract.to_string(),
+            token_id: "genesis-7".to_string(),
+            msg: Binary::default(),
+        },
+        &[],
+    )
+    .unwrap();
+    let resp: NftInfoResponse<Extension> = app
+        .wrap()
+        .query_wasm_smart(
+            contract,
+            &QueryMsg::NftInfo {
+                token_id: "genesis-7".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.token_uri,
+        Some("ipfs://original/genesis-7".to_string())
+    );
+    assert_eq!(resp.extension, original_extension);
+}
@@ -0,0 +1,113 @@
+use cosmwasm_std::{Binary, Decimal, Env, StdResult, Storage, Uint128};
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::state::{
+    Metadata, Rarity, Trait, TraitLayer, BANKED_EXTENSIONS, COLLECTION_SEED, TOKEN_RARITY,
+    TRAIT_LAYERS,
+};
+
+// Rarity tiers by the mean inverse probability of the picked options, checked in order
+const RARITY_TIERS: [(u64, &str); 4] =
+    [(2, "common"), (5, "uncommon"), (20, "rare"), (100, "epic")];
+const TOP_RARITY_TIER: &str = "legendary";
+
+// Seed of the collection, fixed at instantiate from public block data. It is not secret, anyone
+// can derive the traits of an id from it.
+pub fn collection_seed(env: &Env) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update(env.block.chain_id.as_bytes());
+    hasher.update(env.contract.address.as_bytes());
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    Binary::from(hasher.finalize().to_vec())
+}
+
+pub fn validate_trait_layers(layers: &[TraitLayer]) -> Result<(), ContractError> {
+    for layer in layers {
+        let total_weight: u64 = layer.options.iter().map(|o| u64::from(o.weight)).sum();
+        if layer.trait_type.is_empty() || total_weight == 0 {
+            return Err(ContractError::InvalidTraitLayer {
+                trait_type: layer.trait_type.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+// Derive the traits of a token from the collection seed and its id. Each layer picks an option
+// with probability weight / total weight of the layer.
+pub fn derive_traits(
+    seed: &[u8],
+    token_id: Uint128,
+    layers: &[TraitLayer],
+) -> (Vec<Trait>, Rarity) {
+    let mut traits: Vec<Trait> = vec![];
+    let mut score = Decimal::zero();
+
+    for (index, layer) in layers.iter().enumerate() {
+        let total_weight: u64 = layer.options.iter().map(|o| u64::from(o.weight)).sum();
+
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(token_id.u128().to_be_bytes());
+        hasher.update((index as u32).to_be_bytes());
+        let hash = hasher.finalize();
+        let mut roll = u64::from_be_bytes(hash[0..8].try_into().unwrap()) % total_weight;
+
+        for option in layer.options.iter() {
+            let weight = u64::from(option.weight);
+            if roll < weight {
+                traits.push(Trait {
+                    display_type: None,
+                    trait_type: layer.trait_type.clone(),
+                    value: option.value.clone(),
+                });
+                score += Decimal::from_ratio(total_weight, weight);
+                break;
+            }
+            roll -= weight;
+        }
+    }
+
+    let tier = rarity_tier(score, layers.len());
+    (traits, Rarity { score, tier })
+}
+
+fn rarity_tier(score: Decimal, layer_count: usize) -> String {
+    if layer_count == 0 {
+        return RARITY_TIERS[0].1.to_string();
+    }
+    let mean = score / Decimal::from_ratio(layer_count as u128, 1u128);
+    RARITY_TIERS
+        .iter()
+        .find(|(threshold, _)| mean < Decimal::from_ratio(*threshold, 1u128))
+        .map(|(_, tier)| tier.to_string())
+        .unwrap_or_else(|| TOP_RARITY_TIER.to_string())
+}
+
+// Generate the traits of a newly minted token. They are stored with the token's metadata, so
+// the token keeps them when banked and minted again. They are readable from storage right away,
+// a pending reveal does not keep them secret.
+pub fn generate_traits(storage: &mut dyn Storage, token_id: Uint128) -> StdResult<()> {
+    let layers = TRAIT_LAYERS.may_load(storage)?.unwrap_or_default();
+    if layers.is_empty() {
+        return Ok(());
+    }
+    let seed = COLLECTION_SEED.load(storage)?;
+    let (traits, rarity) = derive_traits(&seed, token_id, &layers);
+
+    let key = token_id.to_string();
+    let mut metadata: Metadata = BANKED_EXTENSIONS
+        .may_load(storage, &key)?
+        .flatten()
+        .unwrap_or_default();
+    metadata
+        .attributes
+        .get_or_insert_with(Vec::new)
+        .extend(traits);
+    BANKED_EXTENSIONS.save(storage, &key, &Some(metadata))?;
+    TOKEN_RARITY.save(storage, &key, &rarity)?;
+
+    Ok(())
+}
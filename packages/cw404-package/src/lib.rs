@@ -1,3 +1,14 @@
-pub use crate::query::{TokenInfoResponse, MaxNftSupplyRespone, Cw721TransferExemptResponse};
+pub use crate::query::{
+    AccountMismatch, BankSizeResponse, BankedNftsResponse, CheckInvariantsResponse,
+    CheckRoyaltiesResponse, CurrentPriceResponse, Cw721TransferExemptResponse,
+    Erc721BalanceOfResponse, FeeConfigResponse, FractionStakeResponse,
+    FractionStakingConfigResponse, HolderCountResponse, IsRevealedResponse, LaunchInfoResponse,
+    LockedNftsResponse, MaxNftSupplyRespone, NextFromBankResponse, NftBalanceResponse,
+    NftHoldersResponse, NftOrderResponse, NftStakeInfo, NftStakesResponse,
+    NftStakingConfigResponse, PendingNftRewardsResponse, Pricing, RarityResponse,
+    RenderSvgResponse, RerollFeeResponse, RoyaltiesInfoResponse, SaleInfoResponse,
+    TokenInfoResponse, TotalSupplyAtHeightResponse, VestingResponse, WhitelistClaimedResponse,
+    WhitelistPhaseInfo, WhitelistPhasesResponse,
+};
 
-mod query;
\ No newline at end of file
+mod query;
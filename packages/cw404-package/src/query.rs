@@ -1,5 +1,7 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Timestamp, Uint128};
+use cw20::Denom;
+use cw_utils::{Duration, Expiration};
 
 #[cw_serde]
 pub struct TokenInfoResponse {
@@ -8,7 +10,9 @@ pub struct TokenInfoResponse {
     pub decimals: u8,
     pub total_supply: Uint128,
     pub admin: Addr,
-    pub units: Uint128
+    pub units: Uint128,
+    pub underlying: Option<Denom>,
+    pub wrapped_collection: Option<Addr>,
 }
 
 #[cw_serde]
@@ -19,4 +23,250 @@ pub struct MaxNftSupplyRespone {
 #[cw_serde]
 pub struct Cw721TransferExemptResponse {
     pub state: bool
-}
\ No newline at end of file
+}
+
+#[cw_serde]
+pub struct LaunchInfoResponse {
+    pub trading_enabled: bool,
+    pub launch_height: Option<u64>,
+    pub cooldown_blocks: u64,
+}
+
+#[cw_serde]
+pub struct FeeConfigResponse {
+    pub buy_fee_bps: u16,
+    pub sell_fee_bps: u16,
+    pub transfer_fee_bps: u16,
+    pub treasury: Addr,
+}
+
+// cw2981 royalties
+#[cw_serde]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
+#[cw_serde]
+pub struct RarityResponse {
+    pub token_id: String,
+    pub score: Decimal,
+    pub tier: String,
+}
+
+#[cw_serde]
+pub struct IsRevealedResponse {
+    pub revealed: bool,
+}
+
+#[cw_serde]
+pub struct RenderSvgResponse {
+    pub token_id: String,
+    // base64 encoded svg as a data uri
+    pub data_uri: String,
+}
+
+#[cw_serde]
+pub struct TotalSupplyAtHeightResponse {
+    pub total_supply: Uint128,
+}
+
+#[cw_serde]
+pub struct NftBalanceResponse {
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct Erc721BalanceOfResponse {
+    // Live NFTs held by the owner
+    pub count: u64,
+    // Whole units of the owner's balance
+    pub expected: u64,
+    pub exempt: bool,
+    // Exempt owners are not checked
+    pub in_sync: bool,
+}
+
+#[cw_serde]
+pub struct AccountMismatch {
+    pub address: String,
+    // Whole units of the account's balance
+    pub expected: u64,
+    // Live NFTs held by the account
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct CheckInvariantsResponse {
+    // Non exempt accounts of this page whose NFTs do not match their whole units
+    pub mismatches: Vec<AccountMismatch>,
+    // Last account checked, start_after of the next page
+    pub last_account: Option<String>,
+    pub live_nfts: u64,
+    pub banked_nfts: u64,
+    // Minted NFTs, or the deposited NFTs of a wrapped collection
+    pub nft_count: u64,
+    // Whether live plus banked NFTs equal the NFT count, and live NFTs stay within the max NFT
+    // supply. Banked NFTs can exceed it once a wrapper's supply is withdrawn.
+    pub totals_in_sync: bool,
+}
+
+#[cw_serde]
+pub struct HolderCountResponse {
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct NftHoldersResponse {
+    pub owners: Vec<String>,
+}
+
+#[cw_serde]
+pub struct BankedNftsResponse {
+    // Banked ids in the order they will be handed out
+    pub token_ids: Vec<String>,
+}
+
+#[cw_serde]
+pub struct BankSizeResponse {
+    pub size: u64,
+}
+
+#[cw_serde]
+pub struct NextFromBankResponse {
+    pub token_id: String,
+    // False when the bank is empty and a new id will be minted
+    pub from_bank: bool,
+}
+
+#[cw_serde]
+pub struct RerollFeeResponse {
+    // At most one of them is set, none when reroll is free
+    pub native: Option<Coin>,
+    pub fractions: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct LockedNftsResponse {
+    pub token_ids: Vec<String>,
+}
+
+#[cw_serde]
+pub struct NftOrderResponse {
+    // First leaves first
+    pub token_ids: Vec<String>,
+}
+
+#[cw_serde]
+pub struct NftStakingConfigResponse {
+    pub reward_denom: Denom,
+    pub reward_per_block: Uint128,
+    pub unbonding_period: Duration,
+}
+
+#[cw_serde]
+pub struct NftStakeInfo {
+    pub token_id: String,
+    // Set once unstaked, the NFT stays in custody until it expires
+    pub unbonding: Option<Expiration>,
+    pub pending_rewards: Uint128,
+}
+
+#[cw_serde]
+pub struct NftStakesResponse {
+    pub stakes: Vec<NftStakeInfo>,
+}
+
+#[cw_serde]
+pub struct PendingNftRewardsResponse {
+    pub rewards: Uint128,
+}
+
+#[cw_serde]
+pub struct FractionStakingConfigResponse {
+    pub reward_denom: Denom,
+    pub reward_per_block: Uint128,
+    pub total_staked: Uint128,
+}
+
+#[cw_serde]
+pub struct FractionStakeResponse {
+    pub amount: Uint128,
+    pub pending_rewards: Uint128,
+}
+
+#[cw_serde]
+pub struct SaleInfoResponse {
+    pub denom: String,
+    pub pricing: Pricing,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub per_wallet_cap: Uint128,
+    pub total_cap: Uint128,
+    pub sold: Uint128,
+    pub proceeds: Uint128,
+    pub active: bool,
+}
+
+#[cw_serde]
+pub struct WhitelistPhaseInfo {
+    pub phase_id: u64,
+    pub merkle_root: Binary,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    // None for airdrop phases
+    pub price_per_unit: Option<Coin>,
+    pub proceeds: Uint128,
+    pub active: bool,
+}
+
+#[cw_serde]
+pub struct WhitelistPhasesResponse {
+    pub phases: Vec<WhitelistPhaseInfo>,
+}
+
+#[cw_serde]
+pub struct WhitelistClaimedResponse {
+    pub claimed: Uint128,
+}
+
+// Pricing strategy of the sale, prices are of a whole unit
+#[cw_serde]
+pub enum Pricing {
+    Fixed {
+        price_per_unit: Uint128,
+    },
+    // Decays linearly from the start price to the floor price over decay_blocks from start_height
+    DutchAuction {
+        start_price: Uint128,
+        floor_price: Uint128,
+        start_height: u64,
+        decay_blocks: u64,
+    },
+    // The unit sold after n whole units of the sale costs base_price + increment * n
+    BondingCurve {
+        base_price: Uint128,
+        increment: Uint128,
+    },
+}
+
+#[cw_serde]
+pub struct CurrentPriceResponse {
+    pub denom: String,
+    // Cost of the requested amount of fractions
+    pub price: Uint128,
+}
+
+#[cw_serde]
+pub struct VestingResponse {
+    pub total: Uint128,
+    pub vested: Uint128,
+    pub claimed: Uint128,
+    // Not vested yet
+    pub locked: Uint128,
+}